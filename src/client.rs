@@ -1,12 +1,24 @@
+use futures::{Stream, StreamExt};
+use std::fmt;
+use std::ops::Range;
 pub use subxt::events::Events;
+use subxt::utils::AccountId32;
 pub use subxt::PolkadotConfig;
 
+use crate::error::Error;
 use crate::types::{
-    Contract, ContractResources, Farm, FarmPolicy, Hash, Node, RuntimeEvents, Twin,
+    AccountData, Block, Contract, ContractBillingInformation, ContractLock, ContractResources,
+    ContractState, Farm, FarmCertification, FarmPolicy, Hash, Node, NodeCertification,
+    PricingPolicy, RuntimeEvents, SolutionProvider, Twin,
 };
 
-/// The expected amount of seconds per block.
-const BLOCK_TIME_SECONDS: i64 = 6;
+/// The expected amount of seconds per block on the main network.
+pub const BLOCK_TIME_SECONDS: i64 = 6;
+
+/// The amount of blocks to pass to [`node_last_seen`] to find the last uptime report of a node
+/// which is online. Nodes report their uptime about every 2 hours, which is 1200 blocks, so this
+/// covers two reports.
+pub const NODE_LAST_SEEN_BLOCKS: u32 = 2400;
 
 /// This is the general set of methods which are available on the individual runtime libraries. In
 /// general, methods and types here will adhere to the latest format on the grid, as to have all
@@ -22,105 +34,276 @@ const BLOCK_TIME_SECONDS: i64 = 6;
 /// It is up to the user to ensure that the actual implementation understands how to encode or
 /// decode the data at the given block, or switch to an appropriate client if that is not the case.
 #[async_trait::async_trait]
-pub trait RuntimeClient {
-    /// Get all events in a block.
-    async fn events(
-        &self,
-        block: Option<Hash>,
-    ) -> Result<Vec<RuntimeEvents>, Box<dyn std::error::Error>>;
+pub trait RuntimeClient: Sync {
+    /// Get all events in a block. A block without events storage, e.g. the genesis block, has no
+    /// events.
+    async fn events(&self, block: Option<Hash>) -> Result<Vec<RuntimeEvents>, Error>;
 
     /// Get the hash of a block at the given height. Note that in this case, block is actually the
     /// height rather than the hash to query at.
-    async fn hash_at_height(
-        &self,
-        block: Option<u32>,
-    ) -> Result<Option<Hash>, Box<dyn std::error::Error>>;
+    async fn hash_at_height(&self, block: Option<u32>) -> Result<Option<Hash>, Error>;
+
+    /// Get the block itself, i.e. its header and encoded extrinsics. A block hash in string form
+    /// can be parsed and validated with [`str::parse`], e.g. `"0x..".parse::<Hash>()`.
+    async fn block(&self, block: Option<Hash>) -> Result<Option<Block>, Error>;
+
+    /// Get the hash of the latest finalized block.
+    async fn latest_finalized_hash(&self) -> Result<Hash, Error>;
+
+    /// Get the height of the latest finalized block.
+    async fn latest_block_number(&self) -> Result<u32, Error>;
+
+    /// The expected amount of seconds per block, used to search blocks by timestamp.
+    fn block_time(&self) -> i64 {
+        BLOCK_TIME_SECONDS
+    }
 
     /// Get the on chain timestamp of the block, in seconds since the UNIX epoch.
-    async fn timestamp(&self, block: Option<Hash>) -> Result<u64, Box<dyn std::error::Error>>;
+    async fn timestamp(&self, block: Option<Hash>) -> Result<u64, Error>;
+
+    /// Get the balances of the account. An account which does not exist has no balance.
+    async fn account_data(
+        &self,
+        account: &AccountId32,
+        block: Option<Hash>,
+    ) -> Result<AccountData, Error>;
+
+    /// Get the free balance of the account. Note that part of the free balance can be frozen, so
+    /// it is not necessarily spendable.
+    async fn account_free_balance(
+        &self,
+        account: &AccountId32,
+        block: Option<Hash>,
+    ) -> Result<u128, Error> {
+        Ok(self.account_data(account, block).await?.free)
+    }
 
     /// Get the twin referenced by this ID.
-    async fn twin(
+    async fn twin(&self, id: u32, block: Option<Hash>) -> Result<Option<Twin>, Error>;
+
+    /// Get the twins referenced by these IDs, in the order of the IDs. `None` is returned for IDs
+    /// which don't reference a twin.
+    async fn twins_by_ids(
         &self,
-        id: u32,
+        ids: &[u32],
+        block: Option<Hash>,
+    ) -> Result<Vec<Option<Twin>>, Error> {
+        let mut twins = Vec::with_capacity(ids.len());
+        for id in ids {
+            twins.push(self.twin(*id, block).await?);
+        }
+        Ok(twins)
+    }
+
+    /// Get the ID of the twin bound to this account, if any. This is the inverse of
+    /// [`twin_account`].
+    async fn twin_id_by_account(
+        &self,
+        account: &AccountId32,
         block: Option<Hash>,
-    ) -> Result<Option<Twin>, Box<dyn std::error::Error>>;
+    ) -> Result<Option<u32>, Error>;
+
+    /// Get the ID of the last created twin. IDs are assigned incrementally starting at 1, and are not
+    /// reused when a twin is deleted, so this is the amount of twins ever created rather than the
+    /// amount of twins which currently exist.
+    async fn twin_count(&self, block: Option<Hash>) -> Result<u32, Error>;
 
-    /// Get the amount of twins on the grid.
-    async fn twin_count(&self, block: Option<Hash>) -> Result<u32, Box<dyn std::error::Error>>;
+    /// Get the ID of the farm with the given name.
+    async fn farm_id_by_name(&self, name: &str, block: Option<Hash>) -> Result<Option<u32>, Error>;
 
     /// Get the farm referenced by this ID.
-    async fn farm(
+    async fn farm(&self, id: u32, block: Option<Hash>) -> Result<Option<Farm>, Error>;
+
+    /// Get the farms referenced by these IDs, in the order of the IDs. `None` is returned for IDs
+    /// which don't reference a farm.
+    async fn farms_by_ids(
         &self,
-        id: u32,
+        ids: &[u32],
         block: Option<Hash>,
-    ) -> Result<Option<Farm>, Box<dyn std::error::Error>>;
+    ) -> Result<Vec<Option<Farm>>, Error> {
+        let mut farms = Vec::with_capacity(ids.len());
+        for id in ids {
+            farms.push(self.farm(*id, block).await?);
+        }
+        Ok(farms)
+    }
 
     /// Get the payout address of the farm referenced by this ID.
     async fn farm_payout_address(
         &self,
         id: u32,
         block: Option<Hash>,
-    ) -> Result<Option<String>, Box<dyn std::error::Error>>;
+    ) -> Result<Option<String>, Error>;
+
+    /// Get the ID of the last created farm. IDs are assigned incrementally starting at 1, and are not
+    /// reused when a farm is deleted, so this is the amount of farms ever created rather than the
+    /// amount of farms which currently exist. See [`farms_paged`] to get the existing farms.
+    async fn farm_count(&self, block: Option<Hash>) -> Result<u32, Error>;
 
-    /// Get the amount of farms on the grid.
-    async fn farm_count(&self, block: Option<Hash>) -> Result<u32, Box<dyn std::error::Error>>;
+    /// Get the ID of the node owned by the twin referenced by this ID.
+    async fn node_id_by_twin_id(
+        &self,
+        twin_id: u32,
+        block: Option<Hash>,
+    ) -> Result<Option<u32>, Error>;
 
     /// Get the node referenced by this ID.
-    async fn node(
+    async fn node(&self, id: u32, block: Option<Hash>) -> Result<Option<Node>, Error>;
+
+    /// Get the nodes referenced by these IDs, in the order of the IDs. `None` is returned for IDs
+    /// which don't reference a node.
+    async fn nodes_by_ids(
         &self,
-        id: u32,
+        ids: &[u32],
         block: Option<Hash>,
-    ) -> Result<Option<Node>, Box<dyn std::error::Error>>;
+    ) -> Result<Vec<Option<Node>>, Error> {
+        let mut nodes = Vec::with_capacity(ids.len());
+        for id in ids {
+            nodes.push(self.node(*id, block).await?);
+        }
+        Ok(nodes)
+    }
 
-    /// Get the amount of nodes on the grid.
-    async fn node_count(&self, block: Option<Hash>) -> Result<u32, Box<dyn std::error::Error>>;
+    /// Get the IDs of the nodes in the farm referenced by this ID.
+    async fn farm_nodes(&self, id: u32, block: Option<Hash>) -> Result<Vec<u32>, Error>;
 
-    /// Get the contract referenced by this ID.
-    async fn contract(
+    /// Get the IDs of the active contracts on the node referenced by this ID.
+    async fn node_contracts(&self, id: u32, block: Option<Hash>) -> Result<Vec<u64>, Error>;
+
+    /// Get the ID of the active rent contract on the node referenced by this ID, if the node is
+    /// rented.
+    async fn rent_contract_for_node(
         &self,
-        id: u64,
+        node_id: u32,
         block: Option<Hash>,
-    ) -> Result<Option<Contract>, Box<dyn std::error::Error>>;
+    ) -> Result<Option<u64>, Error>;
+
+    /// Get the ID of the last created node. IDs are assigned incrementally starting at 1, and are not
+    /// reused when a node is deleted, so this is the amount of nodes ever created rather than the
+    /// amount of nodes which currently exist. See [`nodes`] to get the existing nodes.
+    async fn node_count(&self, block: Option<Hash>) -> Result<u32, Error>;
+
+    /// Get the ID of the name contract which registered the given name.
+    async fn contract_id_by_name(
+        &self,
+        name: &str,
+        block: Option<Hash>,
+    ) -> Result<Option<u64>, Error>;
+
+    /// Get the ID of the node contract with the given deployment hash on the node referenced by
+    /// this ID, e.g. to avoid creating a duplicate contract, which the chain rejects.
+    async fn contract_id_by_node_id_and_hash(
+        &self,
+        node_id: u32,
+        deployment_hash: &[u8; 32],
+        block: Option<Hash>,
+    ) -> Result<Option<u64>, Error>;
+
+    /// Get the contract referenced by this ID.
+    async fn contract(&self, id: u64, block: Option<Hash>) -> Result<Option<Contract>, Error>;
 
     /// Get the resources of the contract referenced by this ID.
     async fn contract_resources(
         &self,
         id: u64,
         block: Option<Hash>,
-    ) -> Result<Option<ContractResources>, Box<dyn std::error::Error>>;
+    ) -> Result<Option<ContractResources>, Error>;
+
+    /// Get the billing information of the contract referenced by this ID. This is `None` if the
+    /// contract has never been billed.
+    async fn contract_billing_information(
+        &self,
+        id: u64,
+        block: Option<Hash>,
+    ) -> Result<Option<ContractBillingInformation>, Error>;
+
+    /// Get the funds locked by the contract referenced by this ID. This is `None` if the contract
+    /// has no lock.
+    async fn contract_lock(
+        &self,
+        id: u64,
+        block: Option<Hash>,
+    ) -> Result<Option<ContractLock>, Error>;
+
+    /// Get the solution provider referenced by this ID.
+    async fn solution_provider(
+        &self,
+        id: u64,
+        block: Option<Hash>,
+    ) -> Result<Option<SolutionProvider>, Error>;
 
-    /// Get the amount of contracts on the grid.
-    async fn contract_count(&self, block: Option<Hash>) -> Result<u64, Box<dyn std::error::Error>>;
+    /// Get the ID of the last created contract. IDs are assigned incrementally starting at 1, and
+    /// are not reused when a contract is removed, so this is the amount of contracts ever created
+    /// rather than the amount of contracts which currently exist.
+    async fn contract_count(&self, block: Option<Hash>) -> Result<u64, Error>;
 
     /// Get the farming policy referenced by this ID.
     async fn farming_policy(
         &self,
         id: u32,
         block: Option<Hash>,
-    ) -> Result<Option<FarmPolicy>, Box<dyn std::error::Error>>;
+    ) -> Result<Option<FarmPolicy>, Error>;
+
+    /// Get the ID of the last created farming policy, which is the amount of farming policies ever
+    /// created.
+    async fn farming_policy_count(&self, block: Option<Hash>) -> Result<u32, Error>;
 
-    /// Get the amount of farming policies on the grid.
-    async fn farming_policy_count(
+    /// Get the pricing policy referenced by this ID.
+    async fn pricing_policy(
         &self,
+        id: u32,
         block: Option<Hash>,
-    ) -> Result<u32, Box<dyn std::error::Error>>;
+    ) -> Result<Option<PricingPolicy>, Error>;
+}
+
+/// Errors which can occur while searching the height of the chain at a timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampError {
+    /// The requested timestamp is later than the timestamp of the latest block.
+    Future { requested: i64, latest: i64 },
+    /// The search ended up at a negative height.
+    NegativeHeight { height: u32, delta: i64 },
+    /// The start of a range of timestamps is later than its end.
+    InvalidRange { from: i64, to: i64 },
 }
 
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimestampError::Future { requested, latest } => write!(
+                f,
+                "can't fetch block for future timestamp {} vs latest {}",
+                requested, latest
+            ),
+            TimestampError::NegativeHeight { height, delta } => write!(
+                f,
+                "negative height search (height {} delta {})",
+                height, delta
+            ),
+            TimestampError::InvalidRange { from, to } => {
+                write!(f, "timestamp range starts at {} after its end {}", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimestampError {}
+
 /// Find the height of the chain at the given timestamp.
 ///
 /// This method takes any client, since we assume that the basic storage does not change, and is
 /// therefore consistent across multiple chain versions.
-pub async fn height_at_timestamp(
-    client: &dyn RuntimeClient,
-    ts: i64,
-) -> Result<u32, Box<dyn std::error::Error>> {
+///
+/// If the timestamp is later than the timestamp of the latest block, or the search fails, a
+/// [`TimestampError`] is returned.
+pub async fn height_at_timestamp(client: &dyn RuntimeClient, ts: i64) -> Result<u32, Error> {
     let latest_ts = (client.timestamp(None).await? / 1000) as i64;
     if latest_ts < ts {
-        panic!(
-            "can't fetch block for future timestamp {} vs latest {}",
-            ts, latest_ts
-        );
+        return Err(TimestampError::Future {
+            requested: ts,
+            latest: latest_ts,
+        }
+        .into());
     }
     let mut height = 1;
     let mut last_height = 1;
@@ -134,7 +317,7 @@ pub async fn height_at_timestamp(
         };
         let block_time = (client.timestamp(Some(hash)).await? / 1000) as i64;
         let time_delta = ts - block_time;
-        let block_delta = time_delta / BLOCK_TIME_SECONDS;
+        let block_delta = time_delta / client.block_time();
         if block_delta == 0 {
             if time_delta >= 0 {
                 return Ok(height + 1);
@@ -143,10 +326,11 @@ pub async fn height_at_timestamp(
             }
         }
         if (height as i64 + block_delta) < 0 {
-            panic!(
-                "negative height search (height {} delta {})",
-                height, block_delta
-            );
+            return Err(TimestampError::NegativeHeight {
+                height,
+                delta: block_delta,
+            }
+            .into());
         }
 
         last_height = height;
@@ -154,3 +338,644 @@ pub async fn height_at_timestamp(
         height = (height as i64 + block_delta) as u32;
     }
 }
+
+/// Estimate the amount of seconds per block from the timestamps of the latest finalized block and
+/// the block `sample` blocks before it. The result can be used to configure the block time of a
+/// client.
+pub async fn estimate_block_time(client: &dyn RuntimeClient, sample: u32) -> Result<i64, Error> {
+    let latest = client.latest_block_number().await?;
+    let earlier = latest.saturating_sub(sample).max(1);
+    if earlier == latest {
+        return Err("not enough blocks to estimate the block time".into());
+    }
+    // Look up the hash by height, as the latest finalized block can change after reading its
+    // height.
+    let latest_hash = client
+        .hash_at_height(Some(latest))
+        .await?
+        .ok_or("latest block to estimate the block time not found")?;
+    let earlier_hash = client
+        .hash_at_height(Some(earlier))
+        .await?
+        .ok_or("block to estimate the block time not found")?;
+    let elapsed = client.timestamp(Some(latest_hash)).await? as i64
+        - client.timestamp(Some(earlier_hash)).await? as i64;
+    Ok(elapsed / 1000 / (latest - earlier) as i64)
+}
+
+/// Find the range of heights of the blocks produced between the given timestamps, i.e. `from_ts`
+/// is included but `to_ts` is not. If `to_ts` is later than the timestamp of the latest block, the
+/// range ends after the latest block.
+///
+/// The range can be passed to [`blocks`] to fetch the blocks themselves.
+pub async fn block_range_for_timestamps(
+    client: &dyn RuntimeClient,
+    from_ts: i64,
+    to_ts: i64,
+) -> Result<Range<u32>, Error> {
+    if from_ts > to_ts {
+        return Err(TimestampError::InvalidRange {
+            from: from_ts,
+            to: to_ts,
+        }
+        .into());
+    }
+    let end = match height_at_timestamp(client, to_ts).await {
+        Ok(height) => height,
+        Err(Error::Timestamp(TimestampError::Future { .. })) => {
+            client.latest_block_number().await? + 1
+        }
+        Err(err) => return Err(err),
+    };
+    let start = match height_at_timestamp(client, from_ts).await {
+        Ok(height) => height,
+        Err(Error::Timestamp(TimestampError::Future { .. })) => end,
+        Err(err) => return Err(err),
+    };
+    Ok(start..end)
+}
+
+/// Get the block at the given height. If the chain has not reached this height yet, `None` is
+/// returned.
+pub async fn block_at_height(
+    client: &dyn RuntimeClient,
+    height: u32,
+) -> Result<Option<Block>, Error> {
+    match client.hash_at_height(Some(height)).await? {
+        Some(hash) => client.block(Some(hash)).await,
+        None => Ok(None),
+    }
+}
+
+/// Get all events in the block at the given height. If the chain has not reached this height yet,
+/// no events are returned.
+pub async fn events_at_height(
+    client: &dyn RuntimeClient,
+    height: u32,
+) -> Result<Vec<RuntimeEvents>, Error> {
+    match client.hash_at_height(Some(height)).await? {
+        Some(hash) => client.events(Some(hash)).await,
+        None => Ok(vec![]),
+    }
+}
+
+/// Get the timestamp, in seconds since the UNIX epoch, at which the node referenced by this ID last
+/// reported its uptime as of the given block. The chain doesn't store this, so the
+/// `NodeUptimeReported` events of the `blocks` blocks up to and including the block are searched,
+/// starting at the block itself. `None` is returned if the node didn't report its uptime in these
+/// blocks.
+///
+/// This is expensive: every searched block takes a request for its hash and one for its events,
+/// and blocks of another runtime need its metadata as well. Searching [`NODE_LAST_SEEN_BLOCKS`]
+/// blocks for a node which is offline takes thousands of requests, so keep `blocks` as small as
+/// the use case allows.
+pub async fn node_last_seen(
+    client: &dyn RuntimeClient,
+    node_id: u32,
+    block: Option<Hash>,
+    blocks: u32,
+) -> Result<Option<i64>, Error> {
+    let height = match block {
+        Some(hash) => {
+            client
+                .block(Some(hash))
+                .await?
+                .ok_or_else(|| format!("block {hash:?} not found"))?
+                .header
+                .number
+        }
+        None => client.latest_block_number().await?,
+    };
+    for height in (height.saturating_sub(blocks) + 1..=height).rev() {
+        for event in events_at_height(client, height).await? {
+            if let RuntimeEvents::NodeUptimeReported(id, now, _) = event {
+                if id == node_id {
+                    let now = i64::try_from(now).map_err(|_| {
+                        Error::Other(format!("uptime reported at invalid timestamp {now}"))
+                    })?;
+                    return Ok(Some(now));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Get the blocks in the given range of heights, in order. The blocks are only fetched as the
+/// stream is polled. Errors are yielded as items, after which the stream continues with the next
+/// height. A height the chain has not reached yet yields an error as well.
+pub fn blocks(
+    client: &dyn RuntimeClient,
+    heights: Range<u32>,
+) -> impl Stream<Item = Result<Block, Error>> + '_ {
+    futures::stream::iter(heights).then(move |height| async move {
+        block_at_height(client, height)
+            .await?
+            .ok_or_else(|| format!("no block at height {height}").into())
+    })
+}
+
+/// Get the balances of the account at every `step`th height in the given range, starting at the
+/// first height of the range. A `step` of 0 is treated as 1. A height the chain has not reached
+/// yet results in an error.
+pub async fn balance_history(
+    client: &dyn RuntimeClient,
+    account: &AccountId32,
+    heights: Range<u32>,
+    step: usize,
+) -> Result<Vec<(u32, AccountData)>, Error> {
+    let mut history = Vec::new();
+    for height in heights.step_by(step.max(1)) {
+        let hash = client
+            .hash_at_height(Some(height))
+            .await?
+            .ok_or_else(|| format!("no block at height {height}"))?;
+        history.push((height, client.account_data(account, Some(hash)).await?));
+    }
+    Ok(history)
+}
+
+/// Get up to `limit` farms, starting at the farm with ID `start_id`. IDs of farms which have been
+/// deleted are skipped. The next page starts at the ID following the ID of the last returned farm.
+pub async fn farms_paged(
+    client: &dyn RuntimeClient,
+    start_id: u32,
+    limit: u32,
+    block: Option<Hash>,
+) -> Result<Vec<Farm>, Error> {
+    let last_id = client.farm_count(block).await?;
+    let mut farms = Vec::new();
+    for id in start_id.max(1)..=last_id {
+        if farms.len() >= limit as usize {
+            break;
+        }
+        if let Some(farm) = client.farm(id, block).await? {
+            farms.push(farm);
+        }
+    }
+    Ok(farms)
+}
+
+/// Get up to `limit` contracts, starting at the contract with ID `start_id`. If `state` is set,
+/// only contracts in the same state are returned, regardless of the data of the state, e.g. any
+/// [`ContractState::GracePeriod`] matches. IDs of contracts which have been removed are skipped.
+///
+/// Next to the contracts, the ID to start the next page at is returned, or `None` if all contracts
+/// have been scanned.
+pub async fn contracts_paged(
+    client: &dyn RuntimeClient,
+    start_id: u64,
+    limit: u64,
+    state: Option<&ContractState>,
+    block: Option<Hash>,
+) -> Result<(Vec<Contract>, Option<u64>), Error> {
+    let last_id = client.contract_count(block).await?;
+    let mut contracts = Vec::new();
+    for id in start_id.max(1)..=last_id {
+        if contracts.len() as u64 >= limit {
+            return Ok((contracts, Some(id)));
+        }
+        if let Some(contract) = client.contract(id, block).await? {
+            let matches = state.is_none_or(|state| {
+                std::mem::discriminant(state) == std::mem::discriminant(&contract.state)
+            });
+            if matches {
+                contracts.push(contract);
+            }
+        }
+    }
+    Ok((contracts, None))
+}
+
+/// Get the farm with the given name.
+pub async fn farm_by_name(
+    client: &dyn RuntimeClient,
+    name: &str,
+    block: Option<Hash>,
+) -> Result<Option<Farm>, Error> {
+    match client.farm_id_by_name(name, block).await? {
+        Some(id) => client.farm(id, block).await,
+        None => Ok(None),
+    }
+}
+
+/// Get the name contract which registered the given name.
+pub async fn name_contract_by_name(
+    client: &dyn RuntimeClient,
+    name: &str,
+    block: Option<Hash>,
+) -> Result<Option<Contract>, Error> {
+    match client.contract_id_by_name(name, block).await? {
+        Some(id) => client.contract(id, block).await,
+        None => Ok(None),
+    }
+}
+
+/// Get the account bound to the twin referenced by this ID. This is the inverse of
+/// [`RuntimeClient::twin_id_by_account`].
+pub async fn twin_account(
+    client: &dyn RuntimeClient,
+    twin_id: u32,
+    block: Option<Hash>,
+) -> Result<Option<AccountId32>, Error> {
+    Ok(client
+        .twin(twin_id, block)
+        .await?
+        .map(|twin| twin.account_id))
+}
+
+/// Get the certification of the farm referenced by this ID.
+pub async fn farm_certification(
+    client: &dyn RuntimeClient,
+    farm_id: u32,
+    block: Option<Hash>,
+) -> Result<Option<FarmCertification>, Error> {
+    Ok(client
+        .farm(farm_id, block)
+        .await?
+        .map(|farm| farm.certification))
+}
+
+/// Get the certification of the node referenced by this ID.
+pub async fn node_certification(
+    client: &dyn RuntimeClient,
+    node_id: u32,
+    block: Option<Hash>,
+) -> Result<Option<NodeCertification>, Error> {
+    Ok(client
+        .node(node_id, block)
+        .await?
+        .map(|node| node.certification))
+}
+
+/// Check if the node referenced by this ID is dedicated, i.e. it can only be used by renting it
+/// as a whole. Nodes are dedicated if their farm is. A node which doesn't exist is not dedicated.
+pub async fn is_node_dedicated(
+    client: &dyn RuntimeClient,
+    node_id: u32,
+    block: Option<Hash>,
+) -> Result<bool, Error> {
+    let Some(node) = client.node(node_id, block).await? else {
+        return Ok(false);
+    };
+    Ok(client
+        .farm(node.farm_id, block)
+        .await?
+        .is_some_and(|farm| farm.dedicated_farm))
+}
+
+/// Get the node owned by the twin referenced by this ID.
+pub async fn node_by_twin_id(
+    client: &dyn RuntimeClient,
+    twin_id: u32,
+    block: Option<Hash>,
+) -> Result<Option<Node>, Error> {
+    match client.node_id_by_twin_id(twin_id, block).await? {
+        Some(id) => client.node(id, block).await,
+        None => Ok(None),
+    }
+}
+
+/// Get all nodes on the grid. IDs of nodes which have been deleted are skipped.
+pub async fn nodes(client: &dyn RuntimeClient, block: Option<Hash>) -> Result<Vec<Node>, Error> {
+    let last_id = client.node_count(block).await?;
+    let mut nodes = Vec::new();
+    for id in 1..=last_id {
+        if let Some(node) = client.node(id, block).await? {
+            nodes.push(node);
+        }
+    }
+    Ok(nodes)
+}
+
+/// Get every node ID up to the last created node, paired with the node, or `None` if the node
+/// has been deleted. Unlike [`nodes`], this shows which IDs are gaps. The nodes are fetched with
+/// [`RuntimeClient::nodes_by_ids`].
+pub async fn nodes_with_ids(
+    client: &dyn RuntimeClient,
+    block: Option<Hash>,
+) -> Result<Vec<(u32, Option<Node>)>, Error> {
+    let ids: Vec<u32> = (1..=client.node_count(block).await?).collect();
+    let nodes = client.nodes_by_ids(&ids, block).await?;
+    Ok(ids.into_iter().zip(nodes).collect())
+}
+
+/// Get all nodes in the farm referenced by this ID.
+pub async fn nodes_by_farm(
+    client: &dyn RuntimeClient,
+    farm_id: u32,
+    block: Option<Hash>,
+) -> Result<Vec<Node>, Error> {
+    let mut nodes = Vec::new();
+    for id in client.farm_nodes(farm_id, block).await? {
+        if let Some(node) = client.node(id, block).await? {
+            nodes.push(node);
+        }
+    }
+    Ok(nodes)
+}
+
+/// Get all contracts of the twin referenced by this ID. Since there is no index of contracts by
+/// twin on chain, this scans every contract.
+pub async fn twin_contracts(
+    client: &dyn RuntimeClient,
+    twin_id: u32,
+    block: Option<Hash>,
+) -> Result<Vec<Contract>, Error> {
+    let last_id = client.contract_count(block).await?;
+    let mut contracts = Vec::new();
+    for id in 1..=last_id {
+        if let Some(contract) = client.contract(id, block).await? {
+            if contract.twin_id == twin_id {
+                contracts.push(contract);
+            }
+        }
+    }
+    Ok(contracts)
+}
+
+/// Get the events in a block for which the predicate returns `true`.
+pub async fn events_filtered(
+    client: &dyn RuntimeClient,
+    block: Option<Hash>,
+    pred: impl Fn(&RuntimeEvents) -> bool,
+) -> Result<Vec<RuntimeEvents>, Error> {
+    let mut events = client.events(block).await?;
+    events.retain(pred);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, farm, node, twin, MockClient};
+    use crate::types::{Cause, ContractData, NameContract, RentContract};
+
+    /// The timestamp of the first block, in milliseconds.
+    const GENESIS_MS: u64 = 1_600_000_000_000;
+
+    fn hash(height: u32) -> Hash {
+        Hash::from_low_u64_be(height.into())
+    }
+
+    /// A chain of `height` blocks, which are produced every `block_time` seconds.
+    fn chain(height: u32, block_time: u64) -> MockClient {
+        let mut client = MockClient::new();
+        for h in 1..=height {
+            client.set_hash_at_height(h, hash(h));
+            client.set_timestamp_at(hash(h), GENESIS_MS + u64::from(h) * block_time * 1000);
+        }
+        client.set_latest_block_number(height);
+        client.set_latest_finalized_hash(hash(height));
+        client.set_timestamp(GENESIS_MS + u64::from(height) * block_time * 1000);
+        client
+    }
+
+    fn seconds_at(height: u32, block_time: u64) -> i64 {
+        ((GENESIS_MS + u64::from(height) * block_time * 1000) / 1000) as i64
+    }
+
+    fn contract(contract_id: u64, state: ContractState) -> Contract {
+        let rent_contract = ContractData::RentContract(RentContract { node_id: 1 });
+        mock::contract(contract_id, state, rent_contract)
+    }
+
+    fn ids(contracts: &[Contract]) -> Vec<u64> {
+        contracts.iter().map(|c| c.contract_id).collect()
+    }
+
+    #[tokio::test]
+    async fn height_at_timestamp_between_blocks() {
+        let client = chain(60, 6);
+
+        assert_eq!(
+            height_at_timestamp(&client, seconds_at(50, 6) - 3)
+                .await
+                .unwrap(),
+            50
+        );
+        assert_eq!(
+            height_at_timestamp(&client, seconds_at(50, 6) + 3)
+                .await
+                .unwrap(),
+            51
+        );
+    }
+
+    #[tokio::test]
+    async fn height_at_timestamp_bisects_past_the_latest_block() {
+        // Blocks are slower than expected, so the first estimate is past the latest block.
+        let client = chain(60, 12);
+
+        assert_eq!(
+            height_at_timestamp(&client, seconds_at(50, 12) + 3)
+                .await
+                .unwrap(),
+            51
+        );
+    }
+
+    #[tokio::test]
+    async fn height_at_timestamp_in_the_future() {
+        let client = chain(60, 6);
+        let latest = seconds_at(60, 6);
+
+        let err = height_at_timestamp(&client, latest + 1).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Timestamp(TimestampError::Future { requested, latest: l })
+                if requested == latest + 1 && l == latest
+        ));
+    }
+
+    #[tokio::test]
+    async fn block_range_for_timestamps_is_validated() {
+        let client = chain(60, 6);
+
+        let err = block_range_for_timestamps(&client, 10, 5)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Timestamp(TimestampError::InvalidRange { from: 10, to: 5 })
+        ));
+        assert_eq!(
+            block_range_for_timestamps(&client, seconds_at(20, 6) + 3, seconds_at(61, 6))
+                .await
+                .unwrap(),
+            21..61
+        );
+    }
+
+    #[tokio::test]
+    async fn block_time_is_estimated_at_one_height() {
+        let mut client = chain(60, 6);
+        // Finality advances after the latest height is read.
+        client.set_latest_finalized_hash(hash(61));
+        client.set_timestamp_at(hash(61), GENESIS_MS + 600 * 1000);
+
+        assert_eq!(estimate_block_time(&client, 30).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn contracts_paged_filters_on_state() {
+        let mut client = MockClient::new();
+        client.set_contract(contract(1, ContractState::Created));
+        client.set_contract(contract(2, ContractState::GracePeriod(10)));
+        client.set_contract(contract(4, ContractState::GracePeriod(20)));
+        client.set_contract(contract(5, ContractState::Deleted(Cause::OutOfFunds)));
+        client.set_contract(contract(6, ContractState::GracePeriod(30)));
+
+        let (page, next) = contracts_paged(&client, 0, 10, None, None).await.unwrap();
+        assert_eq!(ids(&page), [1, 2, 4, 5, 6]);
+        assert_eq!(next, None);
+
+        let grace_period = ContractState::GracePeriod(0);
+        let (page, next) = contracts_paged(&client, 1, 2, Some(&grace_period), None)
+            .await
+            .unwrap();
+        assert_eq!(ids(&page), [2, 4]);
+        assert_eq!(next, Some(5));
+
+        let (page, next) = contracts_paged(&client, 5, 2, Some(&grace_period), None)
+            .await
+            .unwrap();
+        assert_eq!(ids(&page), [6]);
+        assert_eq!(next, None);
+    }
+
+    #[tokio::test]
+    async fn twin_account_is_looked_up() {
+        let mut client = MockClient::new();
+        client.set_twin(twin(3));
+
+        assert_eq!(
+            twin_account(&client, 3, None).await.unwrap(),
+            Some(AccountId32([3; 32]))
+        );
+        assert_eq!(twin_account(&client, 4, None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn certifications() {
+        let mut client = MockClient::new();
+        client.set_farm(farm(1));
+        client.set_node(node(2, 1));
+
+        assert!(matches!(
+            farm_certification(&client, 1, None).await.unwrap(),
+            Some(FarmCertification::Gold)
+        ));
+        assert!(farm_certification(&client, 2, None)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            node_certification(&client, 2, None).await.unwrap(),
+            Some(NodeCertification::Certified)
+        ));
+        assert!(node_certification(&client, 1, None)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn nodes_are_dedicated_if_their_farm_is() {
+        let mut client = MockClient::new();
+        client.set_farm(Farm {
+            dedicated_farm: true,
+            ..farm(1)
+        });
+        client.set_farm(farm(2));
+        client.set_node(node(1, 1));
+        client.set_node(node(2, 2));
+        client.set_node(node(3, 3));
+
+        assert!(is_node_dedicated(&client, 1, None).await.unwrap());
+        assert!(!is_node_dedicated(&client, 2, None).await.unwrap());
+        // The farm of the node doesn't exist.
+        assert!(!is_node_dedicated(&client, 3, None).await.unwrap());
+        assert!(!is_node_dedicated(&client, 4, None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn nodes_by_ids_keeps_the_order_of_the_ids() {
+        let mut client = MockClient::new();
+        client.set_node(node(1, 1));
+        client.set_node(node(3, 1));
+
+        let nodes = client.nodes_by_ids(&[3, 2, 1, 3], None).await.unwrap();
+        let ids: Vec<_> = nodes.iter().map(|n| n.as_ref().map(|n| n.id)).collect();
+        assert_eq!(ids, [Some(3), None, Some(1), Some(3)]);
+    }
+
+    #[tokio::test]
+    async fn nodes_with_ids_shows_gaps() {
+        let mut client = MockClient::new();
+        client.set_node(node(1, 1));
+        client.set_node(node(3, 1));
+
+        let ids: Vec<(u32, Option<u32>)> = nodes_with_ids(&client, None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(id, node)| (*id, node.as_ref().map(|n| n.id)))
+            .collect();
+        assert_eq!(ids, [(1, Some(1)), (2, None), (3, Some(3))]);
+        assert_eq!(
+            nodes(&client, None)
+                .await
+                .unwrap()
+                .iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>(),
+            [1, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn node_last_seen_in_uptime_reports() {
+        let mut client = chain(10, 6);
+        client.set_events(vec![
+            RuntimeEvents::NodeUptimeReported(1, 1000, 60),
+            RuntimeEvents::NodeUptimeReported(2, 2000, 120),
+        ]);
+
+        assert_eq!(
+            node_last_seen(&client, 2, None, NODE_LAST_SEEN_BLOCKS)
+                .await
+                .unwrap(),
+            Some(2000)
+        );
+        assert_eq!(
+            node_last_seen(&client, 3, None, NODE_LAST_SEEN_BLOCKS)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(node_last_seen(&client, 2, None, 0).await.unwrap(), None);
+
+        client.set_events(vec![RuntimeEvents::NodeUptimeReported(2, u64::MAX, 120)]);
+        assert!(node_last_seen(&client, 2, None, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn name_contracts_are_found_by_name() {
+        let mut client = MockClient::new();
+        let mut name_contract = contract(1, ContractState::Created);
+        name_contract.contract_type = ContractData::NameContract(NameContract {
+            name: "example".to_string(),
+        });
+        client.set_contract(name_contract);
+
+        let found = name_contract_by_name(&client, "example", None)
+            .await
+            .unwrap();
+        assert_eq!(found.map(|c| c.contract_id), Some(1));
+        assert!(name_contract_by_name(&client, "other", None)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,232 +1,2344 @@
-use crate::client::RuntimeClient;
+use crate::client::{RuntimeClient, BLOCK_TIME_SECONDS};
+use crate::error::Error;
 use crate::runtimes::{
     v115::types::{
-        V115Contract, V115ContractCreatedEvent, V115ContractNruConsumptionReceivedEvent,
-        V115ContractResources, V115ContractUpdatedResourcesEvent, V115Farm, V115FarmingPolicy,
-        V115Node, V115NodeStoredEvent, V115NodeUpdatedEvent, V115NodeUptimeReportedEvent, V115Twin,
+        V115AccountInfo, V115Contract, V115ContractBillingInformation, V115ContractCreatedEvent,
+        V115ContractLock, V115ContractNruConsumptionReceivedEvent, V115ContractResources,
+        V115ContractUpdatedResourcesEvent, V115Farm, V115FarmingPolicy, V115Node,
+        V115NodeStoredEvent, V115NodeUpdatedEvent, V115NodeUptimeReportedEvent, V115PricingPolicy,
+        V115SolutionProvider, V115Twin,
     },
     v123::types::{
-        V123Contract, V123ContractCreatedEvent, V123ContractNruConsumptionReceivedEvent,
-        V123ContractResources, V123ContractUpdatedResourcesEvent, V123Farm, V123FarmingPolicy,
-        V123Node, V123NodeStoredEvent, V123NodeUpdatedEvent, V123NodeUptimeReportedEvent, V123Twin,
+        V123AccountInfo, V123Contract, V123ContractBillingInformation, V123ContractCreatedEvent,
+        V123ContractLock, V123ContractNruConsumptionReceivedEvent, V123ContractResources,
+        V123ContractUpdatedResourcesEvent, V123Farm, V123FarmingPolicy, V123Node,
+        V123NodeStoredEvent, V123NodeUpdatedEvent, V123NodeUptimeReportedEvent, V123PricingPolicy,
+        V123SolutionProvider, V123Twin,
     },
 };
 use crate::types::{
-    Contract, ContractResources, Farm, FarmPolicy, Hash, Node, RuntimeEvents, Twin, CONTRACTS,
-    CONTRACT_CREATED, CONTRACT_ID, FARMING_POLICIES, FARMING_POLICY_ID, FARMS, FARM_ID,
-    FARM_PAYOUT_V2_ADDRESS, NODES, NODE_CONTRACT_RESOURCES, NODE_ID, NODE_STORED, NODE_UPDATED,
-    NODE_UPTIME_REPORTED, NRU_CONSUMPTION_RECEIVED, SMART_CONTRACT_MODULE, TFGRID_MODULE,
-    TIMESTAMP_MODULE, TIMESTAMP_NOW, TWINS, TWIN_ID, UPDATE_USED_RESOURCES,
+    AccountData, Block, BlockEvents, Call, ChainHealth, Contract, ContractBillingInformation,
+    ContractLock, ContractResources, DecodedEvent, DryRunResult, ExtrinsicOutcome, Farm,
+    FarmPolicy, Hash, Header, Interface, Node, NodeRegistration, Phase, PricingPolicy, Resources,
+    RuntimeEvents, RuntimeVersion, Signer, SolutionProvider, StorageKey, Twin, ACCOUNT,
+    ACTIVE_NODE_CONTRACTS, ACTIVE_RENT_CONTRACT_FOR_NODE, ADD_FARM_IP, BALANCES_MODULE, BATCH_ALL,
+    CANCEL_CONTRACT, CONTRACTS, CONTRACT_BILLING_INFORMATION_BY_ID, CONTRACT_CREATED, CONTRACT_ID,
+    CONTRACT_ID_BY_NAME_REGISTRATION, CONTRACT_ID_BY_NODE_ID_AND_HASH, CONTRACT_LOCK, CREATE_NODE,
+    CREATE_NODE_CONTRACT, CREATE_RENT_CONTRACT, DELETE_TWIN, EVENTS, EXTRINSIC_FAILED,
+    FARMING_POLICIES, FARMING_POLICY_ID, FARMS, FARM_ID, FARM_ID_BY_NAME, FARM_PAYOUT_V2_ADDRESS,
+    NODES, NODES_BY_FARM_ID, NODE_CONTRACT_RESOURCES, NODE_ID, NODE_ID_BY_TWIN_ID, NODE_STORED,
+    NODE_UPDATED, NODE_UPTIME_REPORTED, NRU_CONSUMPTION_RECEIVED, PRICING_POLICIES, REMOVE_FARM_IP,
+    REPORT_UPTIME, SMART_CONTRACT_MODULE, SOLUTION_PROVIDERS, SUPPORTED_SPEC_VERSIONS,
+    SYSTEM_MODULE, TFGRID_MODULE, TIMESTAMP_MODULE, TIMESTAMP_NOW, TRANSACTION_PAYMENT_QUERY_INFO,
+    TRANSFER, TRANSFER_KEEP_ALIVE, TWINS, TWIN_ID, TWIN_ID_BY_ACCOUNT_ID, UPDATE_TWIN,
+    UPDATE_USED_RESOURCES, UTILITY_MODULE,
 };
-use subxt::storage::DynamicStorageAddress;
+#[cfg(feature = "sudo")]
+use crate::types::{SUDO, SUDO_MODULE};
+use codec::{Decode, Encode};
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use jsonrpsee::client_transport::ws::{InvalidUri, Uri, WsTransportClientBuilder};
+pub use jsonrpsee::core::client::CertificateStore;
+use jsonrpsee::core::client::{ClientBuilder, ClientT};
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::core::Error as JsonRpseeError;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use scale_info::TypeDef;
+use schnellru::{ByLength, LruMap};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use subxt::ext::frame_metadata::StorageEntryType;
+use subxt::storage::address::StorageMapKey;
+use subxt::storage::utils::storage_address_bytes;
+use subxt::storage::{DynamicStorageAddress, StorageAddress};
 use subxt::{
-    dynamic::Value,
-    // events::Events,
+    config::Header as _,
+    dynamic::{DecodedValueThunk, Value},
+    error::{DispatchError, RpcError},
+    events::{EventDetails, Events},
     rpc::types::{BlockNumber, NumberOrHex},
-    OnlineClient,
-    PolkadotConfig,
+    rpc::{Rpc, Subscription},
+    tx::{DynamicTxPayload, SubmittableExtrinsic},
+    utils::{AccountId32, Encoded},
+    Metadata, OnlineClient, PolkadotConfig,
 };
-use tokio::join;
+use tokio::sync::Mutex;
 
-#[derive(Debug)]
-pub enum Error {
-    ErrorDecodingTwin,
+impl Error {
+    /// Create a [`Error::StorageDecode`] for a value fetched from the storage address.
+    fn storage_decode(
+        address: &DynamicStorageAddress<'_, Value>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Error::StorageDecode {
+            pallet: address.pallet_name().to_string(),
+            item: address.entry_name().to_string(),
+            source: Box::new(source),
+        }
+    }
+}
+
+/// The default time after which a call to the node is aborted, see
+/// [`DynamicClient::with_call_timeout`].
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Configuration of how calls which fail with a transient error are retried. Errors are transient
+/// if retrying could succeed: IO and transport errors, dropped subscriptions, requests which timed
+/// out and connections which have to be restarted. Decoding errors and error responses of the node
+/// are not retried.
+///
+/// The delay before the first retry is `initial_delay`. After every retry, the delay is multiplied
+/// by `backoff_multiplier`. No delay ever exceeds `max_delay`, including the first one.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum amount of times a call is attempted, including the first attempt.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_delay: Duration,
+    /// The factor by which the delay is multiplied after every retry, e.g. `1.5`. It must not be
+    /// negative.
+    pub backoff_multiplier: f64,
+    /// The maximum delay between two attempts. This is [`Duration::MAX`] by default, i.e. the
+    /// delay is not capped.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Attempt every call 5 times, without waiting in between attempts.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_delay: Duration::ZERO,
+            backoff_multiplier: 1.0,
+            max_delay: Duration::MAX,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Get the delay to use before the first retry.
+    fn first_delay(&self) -> Duration {
+        self.initial_delay.min(self.max_delay)
+    }
+
+    /// Get the delay to use after an attempt which was preceded by the given delay.
+    fn next_delay(&self, delay: Duration) -> Duration {
+        Duration::try_from_secs_f64(delay.as_secs_f64() * self.backoff_multiplier)
+            .unwrap_or(Duration::MAX)
+            .min(self.max_delay)
+    }
+}
+
+/// How long to wait after submitting an extrinsic before returning its [`ExtrinsicOutcome`].
+///
+/// Waiting longer gives more guarantees about the extrinsic, at the cost of latency. If the
+/// extrinsic is only submitted, the node accepted it in its transaction pool but it might still
+/// be dropped, and reading the chain right after might not reflect it yet. Once it is included in
+/// a block its outcome is known, though the block might still be reverted. Once the block is
+/// finalized, the outcome is final.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitFor {
+    /// Return as soon as the node accepted the extrinsic. The outcome only holds the extrinsic
+    /// hash.
+    Submitted,
+    /// Wait until the extrinsic is included in a block.
+    #[default]
+    InBlock,
+    /// Wait until the block the extrinsic is included in is finalized.
+    Finalized,
+}
+
+/// The connection status of a [`FinalizedHeadSubscription`] which reconnects when the connection
+/// drops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectStatus {
+    /// The subscription or its connection dropped, and the subscription is being set up again.
+    Reconnecting { attempt: u32 },
+    /// The connection and the subscription have been set up again.
+    Reconnected,
+}
+
+/// A subscription to the headers of newly finalized blocks. The stream ends once the underlying
+/// subscription is closed, and yields an error for every header which can't be decoded.
+///
+/// If reconnecting is enabled with [`FinalizedHeadSubscription::with_reconnect`], a closed
+/// subscription or dropped connection causes the subscription to be set up again instead, after
+/// which it resumes from the latest finalized head. If the connection of the client is broken, the
+/// client fails over to the next reachable endpoint first, in the same way as for calls. Headers of
+/// blocks which are finalized while the connection is down are not yielded.
+///
+/// Dropping the subscription unsubscribes from the node. No background task or thread is spawned
+/// per subscription, so subscriptions can be created and dropped freely.
+pub struct FinalizedHeadSubscription {
+    state: Option<Resubscriber>,
+    stream: Option<BoxStream<'static, Result<Header, Error>>>,
+}
+
+impl FinalizedHeadSubscription {
+    /// Reconnect up to `max_attempts` consecutive times when the subscription breaks, waiting in
+    /// between attempts as configured in the [`RetryPolicy`] of the client. This has no effect
+    /// once the subscription has been polled.
+    pub fn with_reconnect(mut self, max_attempts: u32) -> Self {
+        if let Some(state) = self.state.as_mut() {
+            state.max_reconnects = max_attempts;
+        }
+        self
+    }
+
+    /// Call the handler every time the [`ReconnectStatus`] changes. This has no effect once the
+    /// subscription has been polled.
+    pub fn on_reconnect(
+        mut self,
+        handler: impl Fn(ReconnectStatus) + Send + Sync + 'static,
+    ) -> Self {
+        if let Some(state) = self.state.as_mut() {
+            state.on_reconnect = Some(Box::new(handler));
+        }
+        self
+    }
+}
+
+impl Stream for FinalizedHeadSubscription {
+    type Item = Result<Header, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(state) = self.state.take() {
+            self.stream = Some(
+                futures::stream::unfold(state, |mut state| async move {
+                    let item = state.next().await?.map_err(Error::Rpc);
+                    Some((item, state))
+                })
+                .boxed(),
+            );
+        }
+        match self.stream.as_mut() {
+            Some(stream) => stream.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// A subscription to the known events in newly finalized blocks. This is built on a
+/// [`FinalizedHeadSubscription`], and reconnects in the same way. Once reconnected, the events are
+/// fetched using the new connection.
+pub struct EventSubscription {
+    heads: FinalizedHeadSubscription,
+    stream: Option<BoxStream<'static, Result<BlockEvents, Error>>>,
+}
+
+impl EventSubscription {
+    /// See [`FinalizedHeadSubscription::with_reconnect`].
+    pub fn with_reconnect(mut self, max_attempts: u32) -> Self {
+        self.heads = self.heads.with_reconnect(max_attempts);
+        self
+    }
+
+    /// See [`FinalizedHeadSubscription::on_reconnect`].
+    pub fn on_reconnect(
+        mut self,
+        handler: impl Fn(ReconnectStatus) + Send + Sync + 'static,
+    ) -> Self {
+        self.heads = self.heads.on_reconnect(handler);
+        self
+    }
+}
+
+impl Stream for EventSubscription {
+    type Item = Result<BlockEvents, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(state) = self.heads.state.take() {
+            self.stream = Some(
+                futures::stream::unfold(state, |mut state| async move {
+                    let item =
+                        match state.next().await? {
+                            Ok(header) => {
+                                let hash = header.hash();
+                                state.client.block_events(Some(hash)).await.map(|events| {
+                                    BlockEvents {
+                                        number: header.number,
+                                        hash,
+                                        events,
+                                    }
+                                })
+                            }
+                            Err(err) => Err(err),
+                        };
+                    Some((item.map_err(Error::Rpc), state))
+                })
+                .boxed(),
+            );
+        }
+        match self.stream.as_mut() {
+            Some(stream) => stream.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// The state of a stream returned by [`DynamicClient::events_from`].
+struct Backfill<H, F> {
+    heads: H,
+    fetch: F,
+    policy: RetryPolicy,
+    /// The height of the next block to yield.
+    next: u32,
+    /// The height up to which blocks are known to be finalized.
+    tip: u32,
+    /// The amount of consecutive times fetching the next block failed.
+    failures: u32,
+    /// The delay before fetching the next block again after it failed.
+    delay: Duration,
+}
+
+/// Stream the events of every block starting at the height `start`, see
+/// [`DynamicClient::events_from`]. Blocks up to the height `tip` are finalized, and `heads` yields
+/// the headers of blocks which are finalized later. `fetch` fetches the events of the block at a
+/// height, and is called again as the retry policy specifies if it fails.
+fn backfill<H, F, Fut>(
+    heads: H,
+    start: u32,
+    tip: u32,
+    policy: RetryPolicy,
+    fetch: F,
+) -> BoxStream<'static, Result<BlockEvents, Error>>
+where
+    H: Stream<Item = Result<Header, Error>> + Send + Unpin + 'static,
+    F: Fn(u32) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<BlockEvents, Error>> + Send,
+{
+    let state = Backfill {
+        heads,
+        fetch,
+        delay: policy.first_delay(),
+        policy,
+        next: start,
+        tip,
+        failures: 0,
+    };
+    futures::stream::unfold(state, |mut state| async move {
+        if state.failures >= state.policy.max_attempts.max(1) {
+            return None;
+        }
+        if state.failures > 0 {
+            tokio::time::sleep(state.delay).await;
+            state.delay = state.policy.next_delay(state.delay);
+        }
+        // The subscription can skip heads, so it is only used to learn up to which height blocks
+        // are finalized.
+        while state.next > state.tip {
+            match state.heads.next().await? {
+                Ok(header) => state.tip = state.tip.max(header.number),
+                Err(err) => return Some((Err(err), state)),
+            }
+        }
+        let item = (state.fetch)(state.next).await;
+        if item.is_ok() {
+            state.next += 1;
+            state.failures = 0;
+            state.delay = state.policy.first_delay();
+        } else {
+            state.failures += 1;
+        }
+        Some((item, state))
+    })
+    .boxed()
+}
+
+/// The state of a [`FinalizedHeadSubscription`].
+struct Resubscriber {
+    /// The client the subscription was created with, which shares its connection and endpoints
+    /// with the other clones of the client.
+    client: DynamicClient,
+    subscription: Subscription<Header>,
+    max_reconnects: u32,
+    on_reconnect: Option<Box<dyn Fn(ReconnectStatus) + Send + Sync>>,
+}
+
+impl Resubscriber {
+    /// Get the next header, reconnecting first if the subscription broke.
+    async fn next(&mut self) -> Option<Result<Header, subxt::Error>> {
+        loop {
+            match self.subscription.next().await {
+                Some(Err(err)) if is_transient(&err) && self.max_reconnects > 0 => {}
+                None if self.max_reconnects > 0 => {}
+                item => return item,
+            }
+            self.resubscribe().await?;
+        }
+    }
+
+    /// Set up the subscription again, failing over to the next reachable endpoint if the
+    /// connection of the client is broken. Returns `None` if this failed `max_reconnects`
+    /// consecutive times.
+    async fn resubscribe(&mut self) -> Option<()> {
+        let policy = self.client.retry_policy.clone();
+        let mut delay = policy.first_delay();
+        for attempt in 1..=self.max_reconnects {
+            self.report(ReconnectStatus::Reconnecting { attempt });
+            // The connection might still be fine, or a call on another clone might have failed
+            // over already, in which case only the subscription has to be set up again.
+            let (api, generation) = self.client.connection();
+            let mut subscription = api.rpc().subscribe_finalized_block_headers().await;
+            if subscription.is_err() {
+                self.client.failover(generation).await;
+                subscription = self
+                    .client
+                    .api()
+                    .rpc()
+                    .subscribe_finalized_block_headers()
+                    .await;
+            }
+            if let Ok(subscription) = subscription {
+                self.subscription = subscription;
+                self.report(ReconnectStatus::Reconnected);
+                return Some(());
+            }
+            tokio::time::sleep(delay).await;
+            delay = policy.next_delay(delay);
+        }
+        None
+    }
+
+    fn report(&self, status: ReconnectStatus) {
+        if let Some(handler) = &self.on_reconnect {
+            handler(status);
+        }
+    }
 }
 
+/// A client which decodes data of all supported runtimes, see [`SUPPORTED_SPEC_VERSIONS`].
+///
+/// Cloning the client is cheap. Clones share the connection, the active endpoint, the local nonce
+/// and the block cache, and the client is [`Send`] and [`Sync`], so a clone can be moved into a
+/// spawned task. Data of a block is decoded with the metadata of the runtime at that block without
+/// switching the metadata of the shared connection, so clones can read blocks of different
+/// runtimes concurrently:
+///
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use tfchain_client::client::RuntimeClient;
+/// use tfchain_client::dynamic::DynamicClient;
+///
+/// let client = DynamicClient::new("wss://tfchain.grid.tf:443").await?;
+/// let tasks: Vec<_> = (1..=4)
+///     .map(|id| {
+///         let client = client.clone();
+///         tokio::spawn(async move { client.twin(id, None).await })
+///     })
+///     .collect();
+/// for task in tasks {
+///     println!("{:?}", task.await??);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
 pub struct DynamicClient {
+    /// The connection to the active endpoint.
+    connection: Arc<RwLock<Connection>>,
+    /// Held while the connection is replaced, so clones which fail at the same time fail over
+    /// once.
+    failover_lock: Arc<Mutex<()>>,
+    urls: Vec<String>,
+    certificate_store: CertificateStore,
+    /// The index of the active endpoint in `urls`.
+    endpoint: Arc<AtomicUsize>,
+    retry_policy: RetryPolicy,
+    call_timeout: Duration,
+    signer: Option<Arc<Signer>>,
+    wait_for: WaitFor,
+    /// The nonce to use for the next extrinsic, if nonces are tracked locally. This is [`None`]
+    /// inside the lock if the nonce must be fetched from the chain first.
+    nonce: Option<Arc<Mutex<Option<u32>>>>,
+    block_cache: Option<Arc<std::sync::Mutex<BlockCache>>>,
+    block_time: i64,
+    /// The metadata of runtimes other than the one of the connection, by spec version, see
+    /// [`DynamicClient::api_at`].
+    runtimes: Arc<std::sync::Mutex<HashMap<u32, Metadata>>>,
+}
+
+// Clones of the client are shared between tasks and threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DynamicClient>();
+};
+
+/// The connection to the active endpoint of a [`DynamicClient`].
+struct Connection {
     api: OnlineClient<PolkadotConfig>,
+    /// Incremented every time the connection is replaced, so a caller whose call failed can tell
+    /// if another clone replaced the connection in the meantime.
+    generation: u64,
+}
+
+/// Recently looked up hashes, timestamps and events of blocks, see
+/// [`DynamicClient::with_block_cache`].
+struct BlockCache {
+    hashes: LruMap<u32, Hash>,
+    timestamps: LruMap<Hash, u64>,
+    events: LruMap<Hash, Vec<RuntimeEvents>>,
 }
 
 impl DynamicClient {
-    pub async fn new(url: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let api = OnlineClient::from_url(url).await?;
-        Ok(DynamicClient { api })
+    pub async fn new(url: &str) -> Result<Self, Error> {
+        Self::with_endpoints(&[url]).await
     }
-}
 
-#[async_trait::async_trait]
-impl RuntimeClient for DynamicClient {
-    /// Get all events in a block.
-    async fn events(
+    /// Create a client which connects to the first reachable endpoint of the given endpoints. If
+    /// the connection to the active endpoint fails, the client fails over to the next reachable
+    /// endpoint, and keeps using that one for subsequent calls.
+    pub async fn with_endpoints(urls: &[&str]) -> Result<Self, Error> {
+        Self::with_certificate_store(urls, CertificateStore::Native).await
+    }
+
+    /// Create a client like [`DynamicClient::with_endpoints`], which verifies the certificates of
+    /// `wss://` endpoints against the given certificate store. By default, the native certificate
+    /// store of the system is used, so a private CA can be trusted by adding it to the system
+    /// store. [`CertificateStore::WebPki`] only trusts the bundled Mozilla root certificates.
+    pub async fn with_certificate_store(
+        urls: &[&str],
+        certificate_store: CertificateStore,
+    ) -> Result<Self, Error> {
+        let mut last_err: Error = "no endpoints given".into();
+        for (endpoint, url) in urls.iter().enumerate() {
+            match connect(url, certificate_store).await {
+                Ok(api) => return Ok(Self::with_api(api, urls, certificate_store, endpoint)),
+                Err(err) => last_err = err.into(),
+            }
+        }
+        Err(last_err)
+    }
+
+    fn with_api(
+        api: OnlineClient<PolkadotConfig>,
+        urls: &[&str],
+        certificate_store: CertificateStore,
+        endpoint: usize,
+    ) -> Self {
+        DynamicClient {
+            connection: Arc::new(RwLock::new(Connection { api, generation: 0 })),
+            failover_lock: Arc::default(),
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            certificate_store,
+            endpoint: Arc::new(AtomicUsize::new(endpoint)),
+            retry_policy: RetryPolicy::default(),
+            call_timeout: DEFAULT_CALL_TIMEOUT,
+            signer: None,
+            wait_for: WaitFor::default(),
+            nonce: None,
+            block_cache: None,
+            block_time: BLOCK_TIME_SECONDS,
+            runtimes: Arc::default(),
+        }
+    }
+
+    /// The URL of the active endpoint.
+    pub fn endpoint(&self) -> &str {
+        &self.urls[self.endpoint.load(Ordering::Relaxed)]
+    }
+
+    /// Set up a new connection, fetching the metadata and genesis hash again, e.g. to recover
+    /// after a prolonged outage of the node. The active endpoint is tried first, followed by the
+    /// other endpoints. This is attempted as often as the [`RetryPolicy`] allows, waiting in
+    /// between attempts as configured. The signer and other configuration of the client is kept.
+    ///
+    /// Existing subscriptions keep using the old connection. They only survive an outage if they
+    /// reconnect themselves, see [`FinalizedHeadSubscription::with_reconnect`], after which they use
+    /// the new connection.
+    pub async fn reconnect(&self) -> Result<(), Error> {
+        let policy = &self.retry_policy;
+        let _failover = self.failover_lock.lock().await;
+        let active = self.endpoint.load(Ordering::Relaxed);
+        let mut delay = policy.first_delay();
+        let mut last_err: Error = "the retry policy allows no attempts".into();
+        for attempt in 1..=policy.max_attempts {
+            for offset in 0..self.urls.len() {
+                let endpoint = (active + offset) % self.urls.len();
+                match self.connect_endpoint(endpoint).await {
+                    Ok(api) => {
+                        self.replace_connection(api, endpoint);
+                        return Ok(());
+                    }
+                    Err(err) => last_err = err.into(),
+                }
+            }
+            if attempt < policy.max_attempts {
+                tokio::time::sleep(delay).await;
+                delay = policy.next_delay(delay);
+            }
+        }
+        Err(last_err)
+    }
+
+    /// Use the given [`RetryPolicy`] for all calls made by this client.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Abort a call to the node if it takes longer than the given timeout, e.g. because the
+    /// connection is half open. A call which times out is retried according to the
+    /// [`RetryPolicy`], so the timeout applies to every attempt. Setting up a new connection when
+    /// failing over or reconnecting is aborted after the same timeout. Defaults to 30 seconds.
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = timeout;
+        self
+    }
+
+    /// Use the given [`Signer`] to sign extrinsics submitted by this client.
+    pub fn with_signer(mut self, signer: Signer) -> Self {
+        self.set_signer(signer);
+        self
+    }
+
+    /// Use the given [`Signer`] to sign extrinsics submitted by this client from now on, e.g. to
+    /// turn a read only client into one which can submit extrinsics. If the nonce is tracked
+    /// locally, it is fetched from the chain again for the new signer. Clones of the client made
+    /// before keep using the previous signer.
+    pub fn set_signer(&mut self, signer: Signer) {
+        self.signer = Some(Arc::new(signer));
+        if self.nonce.is_some() {
+            self.nonce = Some(Arc::new(Mutex::new(None)));
+        }
+    }
+
+    /// Check if a [`Signer`] is configured, which is required to submit extrinsics.
+    pub fn has_signer(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    /// Set how long to wait for extrinsics submitted by this client, which is
+    /// [`WaitFor::InBlock`] by default.
+    pub fn with_wait_for(mut self, wait_for: WaitFor) -> Self {
+        self.wait_for = wait_for;
+        self
+    }
+
+    /// Track the nonce of the signer locally, rather than fetching it from the chain for every
+    /// extrinsic. Extrinsics are then signed with consecutive nonces, so they can be submitted in
+    /// quick succession, even if the previous one is not included in a block yet.
+    ///
+    /// This assumes the signer is not used to submit extrinsics outside of this client. If
+    /// submitting an extrinsic fails, the nonce is fetched from the chain again for the next one.
+    pub fn with_local_nonce(mut self) -> Self {
+        self.nonce = Some(Arc::new(Mutex::new(None)));
+        self
+    }
+
+    /// Set the expected amount of seconds per block, which is [`BLOCK_TIME_SECONDS`] by default.
+    /// This should be set when connecting to a chain with a different block time, e.g. using
+    /// [`estimate_block_time`](crate::client::estimate_block_time).
+    pub fn with_block_time(mut self, seconds: i64) -> Self {
+        self.block_time = seconds;
+        self
+    }
+
+    /// Cache the hashes, timestamps and events of up to `capacity` recently looked up blocks,
+    /// which speeds up repeated searches with
+    /// [`height_at_timestamp`](crate::client::height_at_timestamp) and repeated reads of the
+    /// events of a block. Timestamps and events are only cached when requested by block hash, as
+    /// these never change for a given block.
+    ///
+    /// Note that a cached hash is kept even if its block is reverted later on, so this is best
+    /// used when looking up finalized blocks.
+    pub fn with_block_cache(mut self, capacity: u32) -> Self {
+        self.block_cache = Some(Arc::new(std::sync::Mutex::new(BlockCache {
+            hashes: LruMap::new(ByLength::new(capacity)),
+            timestamps: LruMap::new(ByLength::new(capacity)),
+            events: LruMap::new(ByLength::new(capacity)),
+        })));
+        self
+    }
+
+    /// Check the health of the node the client is connected to, e.g. to verify it is reachable
+    /// and synced before starting a long running scan.
+    pub async fn health(&self) -> Result<ChainHealth, Error> {
+        let health = self
+            .retry(|api| async move { api.rpc().system_health().await })
+            .await?;
+        Ok(ChainHealth {
+            peers: health.peers,
+            is_syncing: health.is_syncing,
+            should_have_peers: health.should_have_peers,
+            finalized_height: self.latest_block_number().await?,
+        })
+    }
+
+    /// The hash of the genesis block of the chain, which identifies the network the client is
+    /// connected to.
+    pub fn genesis_hash(&self) -> Hash {
+        self.api().genesis_hash()
+    }
+
+    /// Get the name of the chain the client is connected to, as reported by the node.
+    pub async fn chain_name(&self) -> Result<String, Error> {
+        Ok(self
+            .retry(|api| async move { api.rpc().system_chain().await })
+            .await?)
+    }
+
+    /// Get the version of the runtime at the given block.
+    pub async fn runtime_version(&self, block: Option<Hash>) -> Result<RuntimeVersion, Error> {
+        Ok(self
+            .retry(|api| async move { api.rpc().runtime_version(block).await })
+            .await?)
+    }
+
+    /// Check that the data at the given block can be decoded by this client, i.e. that the spec
+    /// version of the runtime at the block is one of the [`SUPPORTED_SPEC_VERSIONS`]. Data of an
+    /// unsupported runtime might fail to decode, or worse, decode into wrong values.
+    pub async fn check_runtime_version(&self, block: Option<Hash>) -> Result<(), Error> {
+        let spec_version = self.runtime_version(block).await?.spec_version;
+        if !SUPPORTED_SPEC_VERSIONS.contains(&spec_version) {
+            return Err(format!(
+                "unsupported runtime spec version {spec_version}, expected {}..={}",
+                SUPPORTED_SPEC_VERSIONS.start(),
+                SUPPORTED_SPEC_VERSIONS.end()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Subscribe to the headers of newly finalized blocks.
+    pub async fn finalized_heads(&self) -> Result<FinalizedHeadSubscription, Error> {
+        let subscription = self
+            .retry(|api| async move { api.rpc().subscribe_finalized_block_headers().await })
+            .await?;
+        Ok(FinalizedHeadSubscription {
+            state: Some(Resubscriber {
+                client: self.clone(),
+                subscription,
+                max_reconnects: 0,
+                on_reconnect: None,
+            }),
+            stream: None,
+        })
+    }
+
+    /// Wait until the block at the given height is finalized, and return the hash of the first
+    /// finalized block at or past that height. If the chain is already past the height, the hash
+    /// of the latest finalized block is returned right away. Waiting is aborted with an error after
+    /// `timeout`, e.g. if the chain stalls.
+    pub async fn wait_for_block(&self, height: u32, timeout: Duration) -> Result<Hash, Error> {
+        tokio::time::timeout(timeout, self.wait_for_finalized(height))
+            .await
+            .map_err(|_| format!("block {height} not finalized within {timeout:?}"))?
+    }
+
+    /// Wait until the block at the given height is finalized, without a timeout, see
+    /// [`DynamicClient::wait_for_block`].
+    async fn wait_for_finalized(&self, height: u32) -> Result<Hash, Error> {
+        // Subscribe before checking the latest block, so no block is missed in between.
+        let mut heads = self.finalized_heads().await?;
+        let latest = self.latest_finalized_hash().await?;
+        if let Some(header) = self
+            .retry(|api| async move { api.rpc().header(Some(latest)).await })
+            .await?
+        {
+            if header.number >= height {
+                return Ok(latest);
+            }
+        }
+        while let Some(header) = heads.next().await {
+            let header = header?;
+            if header.number >= height {
+                return Ok(header.hash());
+            }
+        }
+        Err("finalized head subscription closed".into())
+    }
+
+    /// Get the known events in the given block, together with the phase in which every event was
+    /// emitted and its topics. The events are decoded with the types of the supported runtimes, see
+    /// [`decode_event_records`](crate::events::decode_event_records).
+    pub async fn event_records(&self, block: Option<Hash>) -> Result<Vec<DecodedEvent>, Error> {
+        let storage_key = self.raw_storage_key(SYSTEM_MODULE, EVENTS, None)?;
+        let data = self
+            .retry(|api| {
+                let storage_key = &storage_key;
+                async move { api.rpc().storage(storage_key, block).await }
+            })
+            .await?;
+        match data {
+            Some(data) => Ok(crate::events::decode_event_records(&data.0)?),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Get the known events emitted while applying the extrinsic at the given index in the block,
+    /// e.g. to show the outcome of a transaction. Events emitted while initializing or finalizing
+    /// the block are not included.
+    pub async fn events_for_extrinsic(
         &self,
+        block: Hash,
+        extrinsic_index: u32,
+    ) -> Result<Vec<RuntimeEvents>, Error> {
+        Ok(self
+            .event_records(Some(block))
+            .await?
+            .into_iter()
+            .filter(|record| record.phase == Phase::ApplyExtrinsic(extrinsic_index))
+            .map(|record| record.event)
+            .collect())
+    }
+
+    /// Subscribe to the known events in newly finalized blocks.
+    pub async fn subscribe_events(&self) -> Result<EventSubscription, Error> {
+        Ok(EventSubscription {
+            heads: self.finalized_heads().await?,
+            stream: None,
+        })
+    }
+
+    /// Stream the known events of every finalized block, starting at the block at height
+    /// `start`, e.g. to resume indexing from a checkpoint. Blocks which are already finalized are
+    /// fetched first, after which the stream follows newly finalized blocks. Every block is
+    /// yielded exactly once and in order, also at the point where the stream catches up.
+    ///
+    /// If fetching the events of a block fails, the error is yielded and the same block is
+    /// fetched again when the stream is polled next, after waiting as the [`RetryPolicy`] of the
+    /// client specifies. The stream ends after the same block failed `max_attempts` times in a
+    /// row.
+    pub async fn events_from(
+        &self,
+        start: u32,
+    ) -> Result<BoxStream<'static, Result<BlockEvents, Error>>, Error> {
+        // Subscribe before fetching the latest height, so no block is missed in between.
+        let heads = self.finalized_heads().await?;
+        let tip = self.latest_block_number().await?;
+        let client = self.clone();
+        let fetch = move |height| {
+            let client = client.clone();
+            async move { client.block_events_at(height).await.map_err(Error::Rpc) }
+        };
+        Ok(backfill(
+            heads,
+            start,
+            tip,
+            self.retry_policy.clone(),
+            fetch,
+        ))
+    }
+
+    /// Get the known events of the block at the given height.
+    async fn block_events_at(&self, height: u32) -> Result<BlockEvents, subxt::Error> {
+        let hash = self
+            .retry(|api| async move {
+                api.rpc()
+                    .block_hash(Some(BlockNumber::from(NumberOrHex::from(height))))
+                    .await
+            })
+            .await?
+            .ok_or_else(|| subxt::Error::Other(format!("no block at height {height}")))?;
+        let events = self.block_events(Some(hash)).await?;
+        Ok(BlockEvents {
+            number: height,
+            hash,
+            events,
+        })
+    }
+
+    /// Get the value of a plain storage item in the given block, decoded as `V`. This allows
+    /// reading storage items for which the client has no dedicated method.
+    pub async fn storage_value<V: Decode>(
+        &self,
+        pallet: &str,
+        item: &str,
         block: Option<Hash>,
-    ) -> Result<Vec<RuntimeEvents>, Box<dyn std::error::Error>> {
-        let (meta, runtime_v) = join!(
-            self.api.rpc().metadata(block),
-            self.api.rpc().runtime_version(block),
-        );
+    ) -> Result<Option<V>, Error> {
+        self.fetch_raw_decoded(pallet, item, None, block).await
+    }
+
+    /// Get the value at `key` in a storage map in the given block, decoded as `V`. Only maps with
+    /// a single hasher are supported. This allows reading storage items for which the client has
+    /// no dedicated method.
+    pub async fn storage_map<K: Encode, V: Decode>(
+        &self,
+        pallet: &str,
+        item: &str,
+        key: &K,
+        block: Option<Hash>,
+    ) -> Result<Option<V>, Error> {
+        self.fetch_raw_decoded(pallet, item, Some(key.encode()), block)
+            .await
+    }
+
+    /// Get the storage key of the entry at `key` in a storage map, hashed as the metadata of the
+    /// active runtime specifies, e.g. to correlate keys returned by RPC calls with entities. Only
+    /// maps with a single hasher are supported.
+    pub fn storage_map_key<K: Encode>(
+        &self,
+        pallet: &str,
+        item: &str,
+        key: &K,
+    ) -> Result<StorageKey, Error> {
+        let key = self.raw_storage_key(pallet, item, Some(key.encode()))?;
+        Ok(StorageKey(key))
+    }
+
+    /// Build the storage key of a plain storage item if `key` is not set, or of the entry at the
+    /// encoded `key` in a storage map with a single hasher.
+    fn raw_storage_key(
+        &self,
+        pallet: &str,
+        item: &str,
+        key: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, Error> {
+        let metadata = self.api().metadata();
+        let entry = metadata.pallet(pallet)?.storage(item)?;
+        let mut storage_key = [
+            sp_core::twox_128(pallet.as_bytes()),
+            sp_core::twox_128(item.as_bytes()),
+        ]
+        .concat();
+        match (&entry.ty, key) {
+            (StorageEntryType::Plain(_), None) => {}
+            (StorageEntryType::Map { hashers, .. }, Some(key)) if hashers.len() == 1 => {
+                StorageMapKey::new(Encoded(key), hashers[0].clone()).to_bytes(&mut storage_key);
+            }
+            _ => return Err(format!("{pallet}::{item} can't be read with the given key").into()),
+        }
+        Ok(storage_key)
+    }
+
+    /// Get the keys of all entries in a storage map in the given block, e.g. to enumerate maps
+    /// which have no counter of their IDs. The keys are fetched in pages. If no block is given,
+    /// all pages are fetched from the latest finalized block, so they are consistent.
+    pub async fn storage_map_keys(
+        &self,
+        pallet: &str,
+        item: &str,
+        block: Option<Hash>,
+    ) -> Result<Vec<StorageKey>, Error> {
+        const PAGE_SIZE: u32 = 1000;
 
-        self.api.set_runtime_version(runtime_v?);
-        self.api.set_metadata(meta?);
+        let metadata = self.api().metadata();
+        if !matches!(
+            metadata.pallet(pallet)?.storage(item)?.ty,
+            StorageEntryType::Map { .. }
+        ) {
+            return Err(format!("{pallet}::{item} is not a storage map").into());
+        }
+        let prefix = [
+            sp_core::twox_128(pallet.as_bytes()),
+            sp_core::twox_128(item.as_bytes()),
+        ]
+        .concat();
+        let block = match block {
+            Some(block) => block,
+            None => self.latest_finalized_hash().await?,
+        };
 
-        let b_events = self.api.events().at(block).await?;
+        let mut keys: Vec<StorageKey> = Vec::new();
+        loop {
+            let start_key = keys.last().map(|key| key.0.clone());
+            let page = self
+                .retry(|api| {
+                    let (prefix, start_key) = (&prefix, &start_key);
+                    async move {
+                        api.rpc()
+                            .storage_keys_paged(
+                                prefix,
+                                PAGE_SIZE,
+                                start_key.as_deref(),
+                                Some(block),
+                            )
+                            .await
+                    }
+                })
+                .await?;
+            let done = page.len() < PAGE_SIZE as usize;
+            keys.extend(page);
+            if done {
+                return Ok(keys);
+            }
+        }
+    }
 
-        let mut events: Vec<RuntimeEvents> = vec![];
-        for event in b_events.iter() {
-            if !event.is_ok() {
-                continue;
+    /// Get a client which encodes and decodes data with the metadata and runtime version of the
+    /// runtime at the given block. Unless that is the runtime `api` already uses, this is a new
+    /// client which shares the connection of `api`, so other calls are not affected. The metadata
+    /// is cached by spec version, so it is only fetched once for every runtime.
+    async fn api_at(
+        &self,
+        api: OnlineClient<PolkadotConfig>,
+        block: Option<Hash>,
+    ) -> Result<OnlineClient<PolkadotConfig>, subxt::Error> {
+        let version = api.rpc().runtime_version(block).await?;
+        let active = api.runtime_version();
+        if (version.spec_version, version.transaction_version)
+            == (active.spec_version, active.transaction_version)
+        {
+            return Ok(api);
+        }
+        let cached = self
+            .runtimes
+            .lock()
+            .unwrap()
+            .get(&version.spec_version)
+            .cloned();
+        let metadata = match cached {
+            Some(metadata) => metadata,
+            None => {
+                let metadata = api.rpc().metadata(block).await?;
+                self.runtimes
+                    .lock()
+                    .unwrap()
+                    .insert(version.spec_version, metadata.clone());
+                metadata
             }
-            let evt = event?;
-
-            match (evt.pallet_name(), evt.variant_name()) {
-                (TFGRID_MODULE, NODE_STORED) => {
-                    if let Ok(Some(evt)) = evt.as_event::<V115NodeStoredEvent>() {
-                        events.push(RuntimeEvents::NodeStoredEvent(evt.0.into()));
-                    } else if let Ok(Some(evt)) = evt.as_event::<V123NodeStoredEvent>() {
-                        events.push(RuntimeEvents::NodeStoredEvent(evt.0.into()));
-                    };
-                }
-                (TFGRID_MODULE, NODE_UPDATED) => {
-                    if let Ok(Some(evt)) = evt.as_event::<V115NodeUpdatedEvent>() {
-                        events.push(RuntimeEvents::NodeUpdatedEvent(evt.0.into()));
-                    } else if let Ok(Some(evt)) = evt.as_event::<V123NodeUpdatedEvent>() {
-                        events.push(RuntimeEvents::NodeUpdatedEvent(evt.0.into()));
-                    };
+        };
+        OnlineClient::from_rpc_client_with(
+            api.genesis_hash(),
+            version,
+            metadata,
+            Arc::new(SharedRpcClient(api.rpc().clone())),
+        )
+    }
+
+    /// Get the known events in the given block, decoded with the metadata of the runtime at the
+    /// block.
+    async fn block_events(&self, block: Option<Hash>) -> Result<Vec<RuntimeEvents>, subxt::Error> {
+        self.retry(|api| async move {
+            let api = self.api_at(api, block).await?;
+            Ok(known_events(&api.events().at(block).await?))
+        })
+        .await
+    }
+
+    /// Sign the call with the configured signer and submit it, waiting as long as `wait_for`
+    /// specifies. The call is built with the metadata of the runtime of the latest block, as the
+    /// arguments of a call can differ between runtimes.
+    ///
+    /// Submitting is not retried, as the extrinsic might have reached the node even though the
+    /// call failed. If the extrinsic is included in a block but fails to dispatch, a
+    /// [`Error::Dispatch`] is returned.
+    async fn submit(
+        &self,
+        call: impl FnOnce(&Metadata) -> DynamicTxPayload<'static>,
+        wait_for: WaitFor,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        let signer = self.signer.as_deref().ok_or(Error::NoSigner)?;
+        self.submit_signed(call, signer, self.nonce.as_deref(), wait_for)
+            .await
+    }
+
+    /// Sign the call with the given signer and submit it, waiting as long as `wait_for`
+    /// specifies. If `nonce` is set, it is used as the local nonce of the signer.
+    async fn submit_signed(
+        &self,
+        call: impl FnOnce(&Metadata) -> DynamicTxPayload<'static>,
+        signer: &Signer,
+        nonce: Option<&Mutex<Option<u32>>>,
+        wait_for: WaitFor,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        let api = self.retry(|api| self.api_at(api, None)).await?;
+        let call = &call(&api.metadata());
+        let tx = api.tx();
+
+        // The lock on the local nonce is held until the extrinsic is accepted by the node, so
+        // extrinsics are submitted in the order of their nonces. The nonce is taken out of the lock
+        // in the meantime, so it is fetched from the chain again if submitting fails.
+        let mut nonce = match nonce {
+            Some(nonce) => Some(nonce.lock().await),
+            None => None,
+        };
+        let next = match nonce.as_deref_mut().map(Option::take) {
+            Some(Some(next)) => Some(next),
+            Some(None) => Some(
+                api.rpc()
+                    .system_account_next_index(signer.account_id())
+                    .await?,
+            ),
+            None => None,
+        };
+        let extrinsic = match next {
+            Some(next) => tx.create_signed_with_nonce(call, signer, next, Default::default())?,
+            None => tx.create_signed(call, signer, Default::default()).await?,
+        };
+
+        let (extrinsic_hash, progress) = match wait_for {
+            WaitFor::Submitted => (extrinsic.submit().await?, None),
+            WaitFor::InBlock | WaitFor::Finalized => {
+                let progress = extrinsic.submit_and_watch().await?;
+                (progress.extrinsic_hash(), Some(progress))
+            }
+        };
+        if let (Some(nonce), Some(next)) = (nonce.as_deref_mut(), next) {
+            *nonce = Some(next + 1);
+        }
+        drop(nonce);
+
+        let in_block = match (progress, wait_for) {
+            (Some(progress), WaitFor::Finalized) => progress.wait_for_finalized().await?,
+            (Some(progress), _) => progress.wait_for_in_block().await?,
+            (None, _) => {
+                return Ok(ExtrinsicOutcome {
+                    extrinsic_hash,
+                    block_hash: None,
+                    events: vec![],
+                })
+            }
+        };
+
+        let mut events = vec![];
+        for evt in in_block.fetch_events().await?.iter() {
+            let evt = evt?;
+            if (evt.pallet_name(), evt.variant_name()) == (SYSTEM_MODULE, EXTRINSIC_FAILED) {
+                return Err(Error::Dispatch {
+                    extrinsic_hash,
+                    block_hash: in_block.block_hash(),
+                    error: Box::new(DispatchError::decode_from(
+                        evt.field_bytes(),
+                        &api.metadata(),
+                    )),
+                });
+            } else if let Some(evt) = decode_event(&evt) {
+                events.push(evt);
+            }
+        }
+
+        Ok(ExtrinsicOutcome {
+            extrinsic_hash,
+            block_hash: Some(in_block.block_hash()),
+            events,
+        })
+    }
+
+    /// Run the given call, retrying it according to the configured [`RetryPolicy`] as long as it
+    /// fails with a transient error.
+    ///
+    /// The call is given the client of the active endpoint. A transient error causes a fail over
+    /// to the next reachable endpoint before retrying, or a new connection to the active endpoint
+    /// if it is the only one. If another clone replaced the connection in the meantime, the retry
+    /// uses that connection instead.
+    ///
+    /// With the `tracing` feature, every retry emits a warning, and the elapsed time and the amount
+    /// of retries are emitted when the call finished.
+    async fn retry<T, F, Fut>(&self, mut call: F) -> Result<T, subxt::Error>
+    where
+        F: FnMut(OnlineClient<PolkadotConfig>) -> Fut,
+        Fut: Future<Output = Result<T, subxt::Error>>,
+    {
+        let policy = &self.retry_policy;
+        let mut delay = policy.first_delay();
+        let mut attempt = 1;
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+        loop {
+            let (api, generation) = self.connection();
+            let res = tokio::time::timeout(self.call_timeout, call(api))
+                .await
+                .unwrap_or_else(|_| Err(timeout_error()));
+            match res {
+                Err(ref err) if is_transient(err) && attempt < policy.max_attempts => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(attempt, endpoint = self.endpoint(), error = %err, "retrying call");
                 }
-                (TFGRID_MODULE, NODE_UPTIME_REPORTED) => {
-                    if let Ok(Some(evt)) = evt.as_event::<V115NodeUptimeReportedEvent>() {
-                        events.push(RuntimeEvents::NodeUptimeReported(evt.0, evt.1, evt.2));
-                    } else if let Ok(Some(evt)) = evt.as_event::<V123NodeUptimeReportedEvent>() {
-                        events.push(RuntimeEvents::NodeUptimeReported(evt.0, evt.1, evt.2));
-                    };
+                res => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        elapsed_ms = start.elapsed().as_millis() as u64,
+                        retries = attempt - 1,
+                        success = res.is_ok(),
+                        "call finished"
+                    );
+                    return res;
                 }
-                (SMART_CONTRACT_MODULE, UPDATE_USED_RESOURCES) => {
-                    if let Ok(Some(evt)) = evt.as_event::<V115ContractUpdatedResourcesEvent>() {
-                        events.push(RuntimeEvents::ContractUsedResourcesUpdated(evt.0.into()));
-                    } else if let Ok(Some(evt)) =
-                        evt.as_event::<V123ContractUpdatedResourcesEvent>()
-                    {
-                        events.push(RuntimeEvents::ContractUsedResourcesUpdated(evt.0.into()));
-                    };
+            }
+            self.failover(generation).await;
+            tokio::time::sleep(delay).await;
+            delay = policy.next_delay(delay);
+            attempt += 1;
+        }
+    }
+
+    /// The client of the connection to the active endpoint.
+    fn api(&self) -> OnlineClient<PolkadotConfig> {
+        self.connection().0
+    }
+
+    /// The client of the connection to the active endpoint, and the generation of the connection
+    /// to pass to [`DynamicClient::failover`] if a call on it fails.
+    fn connection(&self) -> (OnlineClient<PolkadotConfig>, u64) {
+        let connection = self.connection.read().unwrap();
+        (connection.api.clone(), connection.generation)
+    }
+
+    /// Make the connection to the endpoint at the given index the active connection.
+    fn replace_connection(&self, api: OnlineClient<PolkadotConfig>, endpoint: usize) {
+        let mut connection = self.connection.write().unwrap();
+        connection.api = api;
+        connection.generation += 1;
+        self.endpoint.store(endpoint, Ordering::Relaxed);
+    }
+
+    /// Connect to the endpoint at the given index, aborting if this takes longer than the call
+    /// timeout, e.g. because the handshake hangs.
+    async fn connect_endpoint(
+        &self,
+        endpoint: usize,
+    ) -> Result<OnlineClient<PolkadotConfig>, subxt::Error> {
+        tokio::time::timeout(
+            self.call_timeout,
+            connect(&self.urls[endpoint], self.certificate_store),
+        )
+        .await
+        .unwrap_or_else(|_| Err(timeout_error()))
+    }
+
+    /// Connect to the next reachable endpoint after the active one, and make it the active
+    /// endpoint. If no other endpoint is reachable, or there is no other endpoint, the active
+    /// endpoint is connected to again, as its connection might be broken for good, e.g. after the
+    /// node restarted.
+    ///
+    /// `generation` is the generation of the connection which failed. If the connection has been
+    /// replaced since, e.g. because a call on another clone failed at the same time, nothing is
+    /// done and the new connection is used.
+    async fn failover(&self, generation: u64) {
+        let _failover = self.failover_lock.lock().await;
+        if self.connection.read().unwrap().generation != generation {
+            return;
+        }
+        let active = self.endpoint.load(Ordering::Relaxed);
+        for offset in 1..=self.urls.len() {
+            let endpoint = (active + offset) % self.urls.len();
+            if let Ok(api) = self.connect_endpoint(endpoint).await {
+                self.replace_connection(api, endpoint);
+                return;
+            }
+        }
+    }
+
+    /// Fetch the value at the storage address in the given block.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(pallet = address.pallet_name(), item = address.entry_name(), block = ?block)
+        )
+    )]
+    async fn fetch(
+        &self,
+        address: &DynamicStorageAddress<'_, Value>,
+        block: Option<Hash>,
+    ) -> Result<Option<DecodedValueThunk>, subxt::Error> {
+        self.retry(|api| async move { api.storage().at(block).await?.fetch(address).await })
+            .await
+    }
+
+    /// Fetch the value at the storage address in the given block, or the default value if it is
+    /// not set.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(pallet = address.pallet_name(), item = address.entry_name(), block = ?block)
+        )
+    )]
+    async fn fetch_or_default(
+        &self,
+        address: &DynamicStorageAddress<'_, Value>,
+        block: Option<Hash>,
+    ) -> Result<DecodedValueThunk, subxt::Error> {
+        self.retry(|api| async move {
+            api.storage()
+                .at(block)
+                .await?
+                .fetch_or_default(address)
+                .await
+        })
+        .await
+    }
+
+    /// Fetch the value at the storage address in the given block, and decode it as `T`. This
+    /// should only be used for values which have the same encoding in all supported runtimes.
+    async fn fetch_decoded<T: Decode>(
+        &self,
+        address: &DynamicStorageAddress<'_, Value>,
+        block: Option<Hash>,
+    ) -> Result<Option<T>, Error> {
+        match self.fetch(address, block).await.map_err(Error::Rpc)? {
+            Some(result) => Ok(Some(
+                codec::decode_from_bytes(result.into_encoded().into())
+                    .map_err(|err| Error::storage_decode(address, err))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the value at the storage address in the given block, and decode it as either the
+    /// `V115` or the `V123` runtime type, converted into the general type `T`.
+    async fn fetch_versioned<V115, V123, T>(
+        &self,
+        address: &DynamicStorageAddress<'_, Value>,
+        block: Option<Hash>,
+    ) -> Result<Option<T>, Error>
+    where
+        V115: Decode + Into<T>,
+        V123: Decode + Into<T>,
+    {
+        match self.fetch(address, block).await.map_err(Error::Rpc)? {
+            Some(result) => Ok(Some(decode_versioned::<V115, V123, T>(
+                address,
+                &result.into_encoded(),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the values at the storage addresses in the given block with a single request, and
+    /// decode them like [`DynamicClient::fetch_versioned`]. The values are returned in the order
+    /// of the addresses.
+    async fn fetch_many_versioned<V115, V123, T>(
+        &self,
+        addresses: &[DynamicStorageAddress<'_, Value>],
+        block: Option<Hash>,
+    ) -> Result<Vec<Option<T>>, Error>
+    where
+        V115: Decode + Into<T>,
+        V123: Decode + Into<T>,
+    {
+        let metadata = self.api().metadata();
+        let keys = addresses
+            .iter()
+            .map(|address| storage_address_bytes(address, &metadata))
+            .collect::<Result<Vec<_>, _>>()?;
+        let change_sets = self
+            .retry(|api| {
+                let keys = &keys;
+                async move {
+                    api.rpc()
+                        .query_storage_at(keys.iter().map(Vec::as_slice), block)
+                        .await
                 }
-                (SMART_CONTRACT_MODULE, NRU_CONSUMPTION_RECEIVED) => {
-                    if let Ok(Some(evt)) = evt.as_event::<V115ContractNruConsumptionReceivedEvent>()
-                    {
-                        events.push(RuntimeEvents::NruConsumptionReceived(evt.0.into()));
-                    } else if let Ok(Some(evt)) =
-                        evt.as_event::<V123ContractNruConsumptionReceivedEvent>()
-                    {
-                        events.push(RuntimeEvents::NruConsumptionReceived(evt.0.into()));
-                    };
+            })
+            .await
+            .map_err(Error::Rpc)?;
+
+        let values: HashMap<Vec<u8>, Vec<u8>> = change_sets
+            .into_iter()
+            .flat_map(|change_set| change_set.changes)
+            .filter_map(|(key, data)| Some((key.0, data?.0)))
+            .collect();
+        addresses
+            .iter()
+            .zip(&keys)
+            .map(|(address, key)| match values.get(key) {
+                Some(value) => Ok(Some(decode_versioned::<V115, V123, T>(address, value)?)),
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Fetch the value of the storage item in the given block, looking up how the encoded `key` is
+    /// hashed in the metadata, and decode it as `V`.
+    async fn fetch_raw_decoded<V: Decode>(
+        &self,
+        pallet: &str,
+        item: &str,
+        key: Option<Vec<u8>>,
+        block: Option<Hash>,
+    ) -> Result<Option<V>, Error> {
+        let storage_key = self.raw_storage_key(pallet, item, key)?;
+        let data = self
+            .retry(|api| {
+                let storage_key = &storage_key;
+                async move { api.rpc().storage(storage_key, block).await }
+            })
+            .await
+            .map_err(Error::Rpc)?;
+        match data {
+            Some(data) => Ok(Some(V::decode(&mut data.0.as_slice()).map_err(|err| {
+                Error::StorageDecode {
+                    pallet: pallet.to_string(),
+                    item: item.to_string(),
+                    source: Box::new(err),
                 }
-                (SMART_CONTRACT_MODULE, CONTRACT_CREATED) => {
-                    if let Ok(Some(evt)) = evt.as_event::<V115ContractCreatedEvent>() {
-                        events.push(RuntimeEvents::ContractCreated(evt.0.into()));
-                    } else if let Ok(Some(evt)) = evt.as_event::<V123ContractCreatedEvent>() {
-                        events.push(RuntimeEvents::ContractCreated(evt.0.into()));
-                    };
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the numeric value at the storage address in the given block. If the value is not
+    /// set, 0 is returned.
+    async fn fetch_number(
+        &self,
+        address: &DynamicStorageAddress<'_, Value>,
+        block: Option<Hash>,
+    ) -> Result<u128, Error> {
+        let result = self
+            .fetch_or_default(address, block)
+            .await
+            .map_err(Error::Rpc)?
+            .to_value()
+            .map_err(|err| Error::storage_decode(address, err))?;
+
+        Ok(result.as_u128().unwrap_or(0))
+    }
+}
+
+impl DynamicClient {
+    /// Register a new node on the grid, owned by the twin of the signer. The call is encoded as
+    /// the runtime of the latest block expects, e.g. runtime 115 takes the city and country of the
+    /// node as separate arguments rather than as part of the location.
+    pub async fn create_node(&self, node: &NodeRegistration) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::CreateNode(node.clone())).await
+    }
+
+    /// Update the IP of the twin of the signer. A textual IP can be validated before submitting
+    /// anything by parsing it, e.g. `"2001:db8::1".parse()?`.
+    pub async fn update_twin(&self, ip: IpAddr) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::UpdateTwin { ip }).await
+    }
+
+    /// Delete the twin with the given ID, which must be owned by the signer.
+    pub async fn delete_twin(&self, twin_id: u32) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::DeleteTwin { twin_id }).await
+    }
+
+    /// Create a contract for a deployment on the given node, owned by the twin of the signer.
+    pub async fn create_node_contract(
+        &self,
+        node_id: u32,
+        deployment_hash: [u8; 32],
+        deployment_data: Vec<u8>,
+        public_ips: u32,
+        solution_provider_id: Option<u64>,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::CreateNodeContract {
+            node_id,
+            deployment_hash,
+            deployment_data,
+            public_ips,
+            solution_provider_id,
+        })
+        .await
+    }
+
+    /// Rent the given node as a whole, with a contract owned by the twin of the signer. The node
+    /// must be dedicated and not yet rented. If the client waits for the extrinsic to be included
+    /// in a block, the ID of the new contract is in the `ContractCreated` event of the outcome.
+    pub async fn create_rent_contract(
+        &self,
+        node_id: u32,
+        solution_provider_id: Option<u64>,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::CreateRentContract {
+            node_id,
+            solution_provider_id,
+        })
+        .await
+    }
+
+    /// Add a public IP to the farm with the given ID, which must be owned by the twin of the
+    /// signer. The IP must include the prefix length of the subnet, e.g. `185.206.122.33/24`.
+    pub async fn add_farm_ip(
+        &self,
+        farm_id: u32,
+        ip: &str,
+        gateway: Ipv4Addr,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        validate_ip4_subnet(ip)?;
+        self.submit_call(&Call::AddFarmIp {
+            farm_id,
+            ip: ip.to_string(),
+            gateway,
+        })
+        .await
+    }
+
+    /// Remove a public IP from the farm with the given ID, which must be owned by the twin of the
+    /// signer. The IP must be given as it was added, including the prefix length of the subnet.
+    pub async fn remove_farm_ip(&self, farm_id: u32, ip: &str) -> Result<ExtrinsicOutcome, Error> {
+        validate_ip4_subnet(ip)?;
+        self.submit_call(&Call::RemoveFarmIp {
+            farm_id,
+            ip: ip.to_string(),
+        })
+        .await
+    }
+
+    /// Report the uptime in seconds of the node of the twin of the signer. This uses the
+    /// `report_uptime` call, which all supported runtimes provide. Its successor
+    /// `report_uptime_v2` is only available in runtimes newer than the supported ones.
+    pub async fn report_uptime(&self, uptime: u64) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::ReportUptime { uptime }).await
+    }
+
+    /// Cancel the contract with the given ID, which must be owned by the twin of the signer.
+    pub async fn cancel_contract(&self, contract_id: u64) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::CancelContract { contract_id })
+            .await
+    }
+
+    /// Transfer `amount` tokens from the signer to `dest`.
+    pub async fn transfer(
+        &self,
+        dest: &AccountId32,
+        amount: u128,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::Transfer {
+            dest: dest.clone(),
+            amount,
+        })
+        .await
+    }
+
+    /// Transfer `amount` tokens from the signer to `dest`, like [`DynamicClient::transfer`], but
+    /// the chain refuses the transfer if it would drop the balance of the signer below the
+    /// existential deposit, which would reap the account.
+    pub async fn transfer_keep_alive(
+        &self,
+        dest: &AccountId32,
+        amount: u128,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_call(&Call::TransferKeepAlive {
+            dest: dest.clone(),
+            amount,
+        })
+        .await
+    }
+
+    /// Submit the given calls as a single extrinsic, using `batch_all` of the utility pallet. The
+    /// calls are applied atomically: if any of them fails, none of them are applied.
+    pub async fn batch_all(&self, calls: &[Call]) -> Result<ExtrinsicOutcome, Error> {
+        self.submit(
+            |metadata: &Metadata| batch_all_payload(calls, metadata),
+            self.wait_for,
+        )
+        .await
+    }
+
+    /// Submit the call as an extrinsic wrapped in `sudo` of the sudo pallet, so it is dispatched
+    /// with root origin. The signer must be the sudo key of the chain, which is only the case on
+    /// development and test chains.
+    ///
+    /// Note that the extrinsic succeeds even if the wrapped call fails, the result of the call is
+    /// reported in the `Sudid` event instead.
+    #[cfg(feature = "sudo")]
+    pub async fn sudo(&self, call: &Call) -> Result<ExtrinsicOutcome, Error> {
+        let sudo = |metadata: &Metadata| {
+            let (pallet, name, fields) = call_parts(call, metadata);
+            subxt::dynamic::tx(
+                SUDO_MODULE,
+                SUDO,
+                vec![Value::unnamed_variant(
+                    pallet,
+                    [Value::unnamed_variant(name, fields)],
+                )],
+            )
+        };
+        self.submit(sudo, self.wait_for).await
+    }
+
+    /// Submit the call as an extrinsic and wait until the block it is included in is finalized,
+    /// regardless of [`DynamicClient::with_wait_for`]. The outcome holds the events emitted by this
+    /// extrinsic only, e.g. the `ContractCreated` event with the ID of a new contract.
+    pub async fn submit_and_watch(&self, call: &Call) -> Result<ExtrinsicOutcome, Error> {
+        self.submit(|metadata| call_payload(call, metadata), WaitFor::Finalized)
+            .await
+    }
+
+    /// Submit the call as an extrinsic signed by the given signer rather than the configured one,
+    /// reusing the connection of the client, e.g. to submit extrinsics for many accounts. The
+    /// nonce is always fetched from the chain, even if the client tracks the nonce of its own
+    /// signer locally.
+    pub async fn submit_signed_with(
+        &self,
+        signer: &Signer,
+        call: &Call,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        self.submit_signed(
+            |metadata| call_payload(call, metadata),
+            signer,
+            None,
+            self.wait_for,
+        )
+        .await
+    }
+
+    /// Submit the call as an extrinsic, wait until the block it is included in is finalized, and
+    /// then wait until `confirmations` more blocks are finalized on top of it.
+    ///
+    /// A block the extrinsic is included in, see [`WaitFor::InBlock`], can still be reverted by a
+    /// reorg. Once the block is finalized, see [`WaitFor::Finalized`], it can't be reverted
+    /// anymore by the consensus rules of the chain. Waiting for additional confirmations on top of
+    /// that is for users who don't want to act on a transfer until the chain has clearly moved
+    /// past it. With 0 confirmations, this is the same as [`DynamicClient::submit_and_watch`].
+    pub async fn submit_and_confirm(
+        &self,
+        call: &Call,
+        confirmations: u32,
+    ) -> Result<ExtrinsicOutcome, Error> {
+        let outcome = self.submit_and_watch(call).await?;
+        if confirmations > 0 {
+            if let Some(block_hash) = outcome.block_hash {
+                let header = self
+                    .retry(|api| async move { api.rpc().header(Some(block_hash)).await })
+                    .await?
+                    .ok_or("block of the extrinsic not found")?;
+                self.wait_for_finalized(header.number + confirmations)
+                    .await?;
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Estimate the fee the signer would pay to submit the call as an extrinsic, using the
+    /// transaction payment runtime API. Tips are not included.
+    pub async fn estimate_fee(&self, call: &Call) -> Result<u128, Error> {
+        let extrinsic = self.sign(call).await?;
+        let mut params = extrinsic.encoded().to_vec();
+        params.extend((extrinsic.encoded().len() as u32).to_le_bytes());
+        let info = self
+            .retry(|api| {
+                let params = params.clone();
+                async move {
+                    api.rpc()
+                        .state_call(TRANSACTION_PAYMENT_QUERY_INFO, Some(&params), None)
+                        .await
                 }
-                (_m, _e) => (),
+            })
+            .await?;
+
+        // The dispatch info ends with the partial fee, the encoding of the weight before it
+        // differs between runtimes.
+        let fee = info
+            .0
+            .len()
+            .checked_sub(16)
+            .and_then(|start| info.0[start..].try_into().ok())
+            .ok_or("invalid dispatch info returned by the node")?;
+        Ok(u128::from_le_bytes(fee))
+    }
+
+    /// Check if the call would be applied successfully when submitted by the signer, without
+    /// submitting it. This requires the node to expose the unsafe `system_dryRun` RPC method.
+    pub async fn dry_run(&self, call: &Call) -> Result<DryRunResult, Error> {
+        Ok(self.sign(call).await?.dry_run(None).await?)
+    }
+
+    /// Sign the call with the configured signer, using the next nonce of the signer on chain, so
+    /// it can be inspected without submitting it.
+    async fn sign(
+        &self,
+        call: &Call,
+    ) -> Result<SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>, Error> {
+        let signer = self.signer.as_deref().ok_or(Error::NoSigner)?;
+        let api = self.retry(|api| self.api_at(api, None)).await?;
+        Ok(api
+            .tx()
+            .create_signed(
+                &call_payload(call, &api.metadata()),
+                signer,
+                Default::default(),
+            )
+            .await?)
+    }
+
+    /// Submit a single call as an extrinsic.
+    async fn submit_call(&self, call: &Call) -> Result<ExtrinsicOutcome, Error> {
+        self.submit(|metadata| call_payload(call, metadata), self.wait_for)
+            .await
+    }
+}
+
+/// Check that the IP is an IPv4 address with the prefix length of its subnet, e.g.
+/// `185.206.122.33/24`, as public IPs of farms are stored.
+fn validate_ip4_subnet(ip: &str) -> Result<(), Error> {
+    let valid = match ip.split_once('/') {
+        Some((addr, prefix)) => {
+            addr.parse::<Ipv4Addr>().is_ok() && prefix.parse::<u8>().is_ok_and(|p| p <= 32)
+        }
+        None => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidPublicIp(ip.to_string()))
+    }
+}
+
+/// Build the payload of a call, with the arguments the runtime of the metadata expects.
+fn call_payload(call: &Call, metadata: &Metadata) -> DynamicTxPayload<'static> {
+    let (pallet, name, fields) = call_parts(call, metadata);
+    subxt::dynamic::tx(pallet, name, fields)
+}
+
+/// Build the payload of `batch_all` of the utility pallet, which dispatches the calls in order,
+/// with the arguments the runtime of the metadata expects.
+fn batch_all_payload(calls: &[Call], metadata: &Metadata) -> DynamicTxPayload<'static> {
+    let calls = calls.iter().map(|call| {
+        let (pallet, name, fields) = call_parts(call, metadata);
+        Value::unnamed_variant(pallet, [Value::unnamed_variant(name, fields)])
+    });
+    subxt::dynamic::tx(
+        UTILITY_MODULE,
+        BATCH_ALL,
+        vec![Value::unnamed_composite(calls)],
+    )
+}
+
+/// Get the pallet, the name and the fields of a call, with the arguments the runtime of the
+/// metadata expects.
+fn call_parts(call: &Call, metadata: &Metadata) -> (&'static str, &'static str, Vec<Value>) {
+    match call {
+        Call::CreateNode(node) if create_node_takes_location_input(metadata) => (
+            TFGRID_MODULE,
+            CREATE_NODE,
+            vec![
+                Value::u128(node.farm_id.into()),
+                resources_value(&node.resources),
+                Value::named_composite([
+                    ("city", bounded_bytes(node.city.as_bytes())),
+                    ("country", bounded_bytes(node.country.as_bytes())),
+                    ("latitude", bounded_bytes(node.location.latitude.as_bytes())),
+                    (
+                        "longitude",
+                        bounded_bytes(node.location.longitude.as_bytes()),
+                    ),
+                ]),
+                bounded_vec(node.interfaces.iter().map(interface_value)),
+                Value::bool(node.secure_boot),
+                Value::bool(node.virtualized),
+                option_value(
+                    node.serial_number
+                        .as_ref()
+                        .map(|sn| bounded_bytes(sn.as_bytes())),
+                ),
+            ],
+        ),
+        // Older runtimes take the location without the city and country, which are separate
+        // arguments, and a serial number which is empty if unknown.
+        Call::CreateNode(node) => (
+            TFGRID_MODULE,
+            CREATE_NODE,
+            vec![
+                Value::u128(node.farm_id.into()),
+                resources_value(&node.resources),
+                Value::named_composite([
+                    ("longitude", Value::from_bytes(&node.location.longitude)),
+                    ("latitude", Value::from_bytes(&node.location.latitude)),
+                ]),
+                Value::from_bytes(&node.country),
+                Value::from_bytes(&node.city),
+                bounded_vec(node.interfaces.iter().map(interface_value)),
+                Value::bool(node.secure_boot),
+                Value::bool(node.virtualized),
+                Value::from_bytes(node.serial_number.as_deref().unwrap_or_default()),
+            ],
+        ),
+        Call::UpdateTwin { ip } => (
+            TFGRID_MODULE,
+            UPDATE_TWIN,
+            vec![bounded_bytes(ip.to_string().as_bytes())],
+        ),
+        Call::DeleteTwin { twin_id } => (
+            TFGRID_MODULE,
+            DELETE_TWIN,
+            vec![Value::u128((*twin_id).into())],
+        ),
+        Call::AddFarmIp {
+            farm_id,
+            ip,
+            gateway,
+        } => (
+            TFGRID_MODULE,
+            ADD_FARM_IP,
+            vec![
+                Value::u128((*farm_id).into()),
+                bounded_bytes(ip.as_bytes()),
+                bounded_bytes(gateway.to_string().as_bytes()),
+            ],
+        ),
+        Call::RemoveFarmIp { farm_id, ip } => (
+            TFGRID_MODULE,
+            REMOVE_FARM_IP,
+            vec![Value::u128((*farm_id).into()), bounded_bytes(ip.as_bytes())],
+        ),
+        Call::ReportUptime { uptime } => (
+            TFGRID_MODULE,
+            REPORT_UPTIME,
+            vec![Value::u128((*uptime).into())],
+        ),
+        Call::CreateNodeContract {
+            node_id,
+            deployment_hash,
+            deployment_data,
+            public_ips,
+            solution_provider_id,
+        } => (
+            SMART_CONTRACT_MODULE,
+            CREATE_NODE_CONTRACT,
+            vec![
+                Value::u128((*node_id).into()),
+                Value::unnamed_composite(deployment_hash.iter().map(|b| Value::u128((*b).into()))),
+                bounded_bytes(deployment_data),
+                Value::u128((*public_ips).into()),
+                option_value(solution_provider_id.map(|id| Value::u128(id.into()))),
+            ],
+        ),
+        Call::CreateRentContract {
+            node_id,
+            solution_provider_id,
+        } => (
+            SMART_CONTRACT_MODULE,
+            CREATE_RENT_CONTRACT,
+            vec![
+                Value::u128((*node_id).into()),
+                option_value(solution_provider_id.map(|id| Value::u128(id.into()))),
+            ],
+        ),
+        Call::CancelContract { contract_id } => (
+            SMART_CONTRACT_MODULE,
+            CANCEL_CONTRACT,
+            vec![Value::u128((*contract_id).into())],
+        ),
+        Call::Transfer { dest, amount } => (
+            BALANCES_MODULE,
+            TRANSFER,
+            vec![address_value(dest), Value::u128(*amount)],
+        ),
+        Call::TransferKeepAlive { dest, amount } => (
+            BALANCES_MODULE,
+            TRANSFER_KEEP_ALIVE,
+            vec![address_value(dest), Value::u128(*amount)],
+        ),
+    }
+}
+
+/// Check if the `create_node` call of the runtime of the metadata takes the location as a
+/// `LocationInput`, which includes the city and country of the node. Older runtimes, e.g. runtime
+/// 115, take the city and country as separate arguments instead.
+fn create_node_takes_location_input(metadata: &Metadata) -> bool {
+    let Some(calls) = metadata
+        .pallet(TFGRID_MODULE)
+        .ok()
+        .and_then(|pallet| pallet.call_ty_id())
+        .and_then(|id| metadata.resolve_type(id))
+    else {
+        return true;
+    };
+    let TypeDef::Variant(calls) = calls.type_def() else {
+        return true;
+    };
+    calls
+        .variants()
+        .iter()
+        .find(|call| call.name() == CREATE_NODE)
+        .is_none_or(|call| {
+            !call
+                .fields()
+                .iter()
+                .any(|field| field.name().is_some_and(|name| name == "city"))
+        })
+}
+
+/// Decode the encoded value at the storage address as either the `V115` or the `V123` runtime type,
+/// converted into the general type `T`.
+fn decode_versioned<V115, V123, T>(
+    address: &DynamicStorageAddress<'_, Value>,
+    encoded: &[u8],
+) -> Result<T, Error>
+where
+    V115: Decode + Into<T>,
+    V123: Decode + Into<T>,
+{
+    if let Ok(value) = V115::decode(&mut &*encoded) {
+        Ok(value.into())
+    } else {
+        let v123 =
+            V123::decode(&mut &*encoded).map_err(|err| Error::storage_decode(address, err))?;
+        Ok(v123.into())
+    }
+}
+
+/// The error of a call or connection attempt which timed out. It is transient, so the call is
+/// retried.
+fn timeout_error() -> subxt::Error {
+    subxt::Error::Rpc(RpcError::ClientError(Box::new(
+        JsonRpseeError::RequestTimeout,
+    )))
+}
+
+/// Connect to the node at the URL, verifying the certificate of a `wss://` or `https://` URL
+/// against the given certificate store. An `http://` or `https://` URL uses JSON-RPC over HTTP,
+/// which avoids setting up a websocket for one-off queries but doesn't support subscriptions. Any
+/// other URL is connected to over a websocket.
+async fn connect(
+    url: &str,
+    certificate_store: CertificateStore,
+) -> Result<OnlineClient<PolkadotConfig>, subxt::Error> {
+    let transport_err =
+        |err: JsonRpseeError| subxt::Error::Rpc(RpcError::ClientError(Box::new(err)));
+    if url.starts_with("http://") || url.starts_with("https://") {
+        let client = HttpClientBuilder::default()
+            .certificate_store(certificate_store)
+            .build(url)
+            .map_err(transport_err)?;
+        return online_client(HttpRpcClient(client)).await;
+    }
+    let uri: Uri = url
+        .parse()
+        .map_err(|err: InvalidUri| transport_err(JsonRpseeError::Transport(err.into())))?;
+    let (sender, receiver) = WsTransportClientBuilder::default()
+        .certificate_store(certificate_store)
+        .build(uri)
+        .await
+        .map_err(|err| transport_err(JsonRpseeError::Transport(err.into())))?;
+    // The same client subxt builds when connecting to a URL.
+    let client = ClientBuilder::default()
+        .max_notifs_per_subscription(4096)
+        .build_with_tokio(sender, receiver);
+    online_client(client).await
+}
+
+/// Create a client on top of the RPC client, fetching the metadata and runtime version.
+async fn online_client(
+    client: impl subxt::rpc::RpcClientT,
+) -> Result<OnlineClient<PolkadotConfig>, subxt::Error> {
+    #[cfg(feature = "tracing")]
+    let client = TracingRpcClient(client);
+    OnlineClient::from_rpc_client(Arc::new(client)).await
+}
+
+/// Parameters which are already serialized by subxt.
+struct RawParams(Option<Box<subxt::rpc::RawValue>>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<subxt::rpc::RawValue>>, JsonRpseeError> {
+        Ok(self.0)
+    }
+}
+
+/// An RPC client using JSON-RPC over HTTP. Subscriptions are not supported over HTTP, and fail
+/// with an error.
+struct HttpRpcClient(HttpClient);
+
+impl subxt::rpc::RpcClientT for HttpRpcClient {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+    ) -> subxt::rpc::RpcFuture<'a, Box<subxt::rpc::RawValue>> {
+        Box::pin(async move {
+            self.0
+                .request(method, RawParams(params))
+                .await
+                .map_err(|err| RpcError::ClientError(Box::new(err)))
+        })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        _: Option<Box<subxt::rpc::RawValue>>,
+        _: &'a str,
+    ) -> subxt::rpc::RpcFuture<'a, subxt::rpc::RpcSubscription> {
+        Box::pin(async move {
+            Err(RpcError::ClientError(
+                format!("{sub} is a subscription, which requires a websocket connection rather than HTTP")
+                    .into(),
+            ))
+        })
+    }
+}
+
+/// An RPC client which sends requests over the connection of another client, so clients with
+/// different metadata can share a connection.
+struct SharedRpcClient(Rpc<PolkadotConfig>);
+
+impl subxt::rpc::RpcClientT for SharedRpcClient {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+    ) -> subxt::rpc::RpcFuture<'a, Box<subxt::rpc::RawValue>> {
+        self.0.request_raw(method, params)
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+        unsub: &'a str,
+    ) -> subxt::rpc::RpcFuture<'a, subxt::rpc::RpcSubscription> {
+        self.0.subscribe_raw(sub, params, unsub)
+    }
+}
+
+/// An RPC client which logs every request with its raw parameters and response at debug level,
+/// to diagnose data which is not decoded as expected.
+#[cfg(feature = "tracing")]
+struct TracingRpcClient<C>(C);
+
+#[cfg(feature = "tracing")]
+impl<C: subxt::rpc::RpcClientT> subxt::rpc::RpcClientT for TracingRpcClient<C> {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+    ) -> subxt::rpc::RpcFuture<'a, Box<subxt::rpc::RawValue>> {
+        Box::pin(async move {
+            tracing::debug!(
+                method,
+                params = params.as_ref().map_or("[]", |params| params.get()),
+                "rpc request"
+            );
+            let res = self.0.request_raw(method, params).await;
+            match &res {
+                Ok(response) => tracing::debug!(method, response = response.get(), "rpc response"),
+                Err(err) => tracing::debug!(method, error = %err, "rpc request failed"),
+            }
+            res
+        })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+        unsub: &'a str,
+    ) -> subxt::rpc::RpcFuture<'a, subxt::rpc::RpcSubscription> {
+        tracing::debug!(
+            method = sub,
+            params = params.as_ref().map_or("[]", |params| params.get()),
+            "rpc subscription"
+        );
+        self.0.subscribe_raw(sub, params, unsub)
+    }
+}
+
+/// Decode all events which are one of the [`RuntimeEvents`] this client knows about, skipping
+/// any other events and events which can't be decoded.
+fn known_events(events: &Events<PolkadotConfig>) -> Vec<RuntimeEvents> {
+    events
+        .iter()
+        .filter_map(|evt| decode_event(&evt.ok()?))
+        .collect()
+}
+
+/// Decode an event into one of the [`RuntimeEvents`] this client knows about, trying all
+/// supported runtime versions. Returns [`None`] for any other event.
+fn decode_event(evt: &EventDetails) -> Option<RuntimeEvents> {
+    match (evt.pallet_name(), evt.variant_name()) {
+        (TFGRID_MODULE, NODE_STORED) => {
+            if let Ok(Some(evt)) = evt.as_event::<V115NodeStoredEvent>() {
+                return Some(RuntimeEvents::NodeStoredEvent(evt.0.into()));
+            } else if let Ok(Some(evt)) = evt.as_event::<V123NodeStoredEvent>() {
+                return Some(RuntimeEvents::NodeStoredEvent(evt.0.into()));
+            };
+        }
+        (TFGRID_MODULE, NODE_UPDATED) => {
+            if let Ok(Some(evt)) = evt.as_event::<V115NodeUpdatedEvent>() {
+                return Some(RuntimeEvents::NodeUpdatedEvent(evt.0.into()));
+            } else if let Ok(Some(evt)) = evt.as_event::<V123NodeUpdatedEvent>() {
+                return Some(RuntimeEvents::NodeUpdatedEvent(evt.0.into()));
+            };
+        }
+        (TFGRID_MODULE, NODE_UPTIME_REPORTED) => {
+            if let Ok(Some(evt)) = evt.as_event::<V115NodeUptimeReportedEvent>() {
+                return Some(RuntimeEvents::NodeUptimeReported(evt.0, evt.1, evt.2));
+            } else if let Ok(Some(evt)) = evt.as_event::<V123NodeUptimeReportedEvent>() {
+                return Some(RuntimeEvents::NodeUptimeReported(evt.0, evt.1, evt.2));
+            };
+        }
+        (SMART_CONTRACT_MODULE, UPDATE_USED_RESOURCES) => {
+            if let Ok(Some(evt)) = evt.as_event::<V115ContractUpdatedResourcesEvent>() {
+                return Some(RuntimeEvents::ContractUsedResourcesUpdated(evt.0.into()));
+            } else if let Ok(Some(evt)) = evt.as_event::<V123ContractUpdatedResourcesEvent>() {
+                return Some(RuntimeEvents::ContractUsedResourcesUpdated(evt.0.into()));
+            };
+        }
+        (SMART_CONTRACT_MODULE, NRU_CONSUMPTION_RECEIVED) => {
+            if let Ok(Some(evt)) = evt.as_event::<V115ContractNruConsumptionReceivedEvent>() {
+                return Some(RuntimeEvents::NruConsumptionReceived(evt.0.into()));
+            } else if let Ok(Some(evt)) = evt.as_event::<V123ContractNruConsumptionReceivedEvent>()
+            {
+                return Some(RuntimeEvents::NruConsumptionReceived(evt.0.into()));
+            };
+        }
+        (SMART_CONTRACT_MODULE, CONTRACT_CREATED) => {
+            if let Ok(Some(evt)) = evt.as_event::<V115ContractCreatedEvent>() {
+                return Some(RuntimeEvents::ContractCreated(evt.0.into()));
+            } else if let Ok(Some(evt)) = evt.as_event::<V123ContractCreatedEvent>() {
+                return Some(RuntimeEvents::ContractCreated(evt.0.into()));
+            };
+        }
+        (_m, _e) => (),
+    }
+    None
+}
+
+/// Build the value of a `BoundedVec` with the given items. The inner sequence is wrapped
+/// explicitly, as a sequence with a single item would otherwise be encoded as if it was the
+/// `BoundedVec` itself.
+fn bounded_vec(items: impl IntoIterator<Item = Value>) -> Value {
+    Value::unnamed_composite([Value::unnamed_composite(items)])
+}
+
+/// Build the value of a `BoundedVec<u8>`.
+fn bounded_bytes(bytes: &[u8]) -> Value {
+    bounded_vec(bytes.iter().map(|b| Value::u128((*b).into())))
+}
+
+/// Build the value of an `Option`.
+fn option_value(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::unnamed_variant("Some", [value]),
+        None => Value::unnamed_variant("None", []),
+    }
+}
+
+/// Build the value of a `MultiAddress` pointing to the given account.
+fn address_value(account: &AccountId32) -> Value {
+    let bytes = account.0.iter().map(|b| Value::u128((*b).into()));
+    Value::unnamed_variant(
+        "Id",
+        [Value::unnamed_composite([Value::unnamed_composite(bytes)])],
+    )
+}
+
+fn resources_value(resources: &Resources) -> Value {
+    Value::named_composite([
+        ("hru", Value::u128(resources.hru.into())),
+        ("sru", Value::u128(resources.sru.into())),
+        ("cru", Value::u128(resources.cru.into())),
+        ("mru", Value::u128(resources.mru.into())),
+    ])
+}
+
+fn interface_value(interface: &Interface) -> Value {
+    Value::named_composite([
+        ("name", bounded_bytes(interface.name.as_bytes())),
+        ("mac", bounded_bytes(interface.mac.as_bytes())),
+        (
+            "ips",
+            bounded_vec(interface.ips.iter().map(|ip| bounded_bytes(ip.as_bytes()))),
+        ),
+    ])
+}
+
+/// Check if an error is transient, i.e. retrying the call which caused it could succeed. The
+/// following errors are considered transient:
+///
+/// - [`subxt::Error::Io`]
+/// - [`RpcError::SubscriptionDropped`]
+/// - [`RpcError::ClientError`] caused by a [`JsonRpseeError::Transport`],
+///   [`JsonRpseeError::RestartNeeded`], [`JsonRpseeError::RequestTimeout`] or an IO error.
+///
+/// Every other error, e.g. a decoding error or an error response from the node, is not transient.
+fn is_transient(err: &subxt::Error) -> bool {
+    match err {
+        subxt::Error::Io(_) => true,
+        subxt::Error::Rpc(RpcError::SubscriptionDropped) => true,
+        subxt::Error::Rpc(RpcError::ClientError(err)) => {
+            if let Some(err) = err.downcast_ref::<JsonRpseeError>() {
+                matches!(
+                    err,
+                    JsonRpseeError::Transport(_)
+                        | JsonRpseeError::RestartNeeded(_)
+                        | JsonRpseeError::RequestTimeout
+                )
+            } else {
+                err.is::<std::io::Error>()
+            }
+        }
+        _ => false,
+    }
+}
+
+#[async_trait::async_trait]
+impl RuntimeClient for DynamicClient {
+    /// Get all events in a block.
+    async fn events(&self, block: Option<Hash>) -> Result<Vec<RuntimeEvents>, Error> {
+        let cache = self.block_cache.as_ref().zip(block);
+        if let Some((cache, hash)) = cache {
+            if let Some(events) = cache.lock().unwrap().events.get(&hash) {
+                return Ok(events.clone());
             }
         }
+        let events = self.block_events(block).await?;
+        if let Some((cache, hash)) = cache {
+            cache.lock().unwrap().events.insert(hash, events.clone());
+        }
         Ok(events)
     }
 
     /// Get the hash of a block at the given height. Note that in this case, block is actually the
     /// height rather than the hash to query at.
-    async fn hash_at_height(
-        &self,
-        block: Option<u32>,
-    ) -> Result<Option<Hash>, Box<dyn std::error::Error>> {
+    async fn hash_at_height(&self, block: Option<u32>) -> Result<Option<Hash>, Error> {
+        let cache = self.block_cache.as_ref().zip(block);
+        if let Some((cache, height)) = cache {
+            if let Some(hash) = cache.lock().unwrap().hashes.get(&height) {
+                return Ok(Some(*hash));
+            }
+        }
+        let hash = self
+            .retry(|api| async move {
+                api.rpc()
+                    .block_hash(block.map(|block| BlockNumber::from(NumberOrHex::from(block))))
+                    .await
+            })
+            .await?;
+        if let (Some((cache, height)), Some(hash)) = (cache, hash) {
+            cache.lock().unwrap().hashes.insert(height, hash);
+        }
+        Ok(hash)
+    }
+
+    /// Get the block itself, i.e. its header and encoded extrinsics. A block hash in string form
+    /// can be parsed and validated with [`str::parse`], e.g. `"0x..".parse::<Hash>()`.
+    async fn block(&self, block: Option<Hash>) -> Result<Option<Block>, Error> {
         Ok(self
-            .api
-            .rpc()
-            .block_hash(block.map(|block| BlockNumber::from(NumberOrHex::from(block))))
+            .retry(|api| async move { api.rpc().block(block).await })
+            .await?
+            .map(|response| response.block))
+    }
+
+    /// Get the hash of the latest finalized block.
+    async fn latest_finalized_hash(&self) -> Result<Hash, Error> {
+        Ok(self
+            .retry(|api| async move { api.rpc().finalized_head().await })
             .await?)
     }
 
+    /// Get the height of the latest finalized block.
+    async fn latest_block_number(&self) -> Result<u32, Error> {
+        let hash = self.latest_finalized_hash().await?;
+        let header = self
+            .retry(|api| async move { api.rpc().header(Some(hash)).await })
+            .await?
+            .ok_or("header of the latest finalized block not found")?;
+        Ok(header.number)
+    }
+
+    /// The expected amount of seconds per block, as configured with
+    /// [`DynamicClient::with_block_time`].
+    fn block_time(&self) -> i64 {
+        self.block_time
+    }
+
     /// Get the on chain timestamp of the block, in seconds since the UNIX epoch.
-    async fn timestamp(&self, block: Option<Hash>) -> Result<u64, Box<dyn std::error::Error>> {
+    async fn timestamp(&self, block: Option<Hash>) -> Result<u64, Error> {
+        let cache = self.block_cache.as_ref().zip(block);
+        if let Some((cache, hash)) = cache {
+            if let Some(timestamp) = cache.lock().unwrap().timestamps.get(&hash) {
+                return Ok(*timestamp);
+            }
+        }
         let storage_address: DynamicStorageAddress<Value> =
             subxt::dynamic::storage(TIMESTAMP_MODULE, TIMESTAMP_NOW, vec![]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch_or_default(&storage_address)
-            .await?
-            .to_value()?;
-
-        Ok(result.as_u128().map_or(0, |x| x as u64))
+        let timestamp = self.fetch_number(&storage_address, block).await? as u64;
+        if let Some((cache, hash)) = cache {
+            cache.lock().unwrap().timestamps.insert(hash, timestamp);
+        }
+        Ok(timestamp)
     }
 
-    /// Get the twin referenced by this ID.
-    async fn twin(
+    /// Get the balances of the account. An account which does not exist has no balance.
+    async fn account_data(
         &self,
-        id: u32,
+        account: &AccountId32,
         block: Option<Hash>,
-    ) -> Result<Option<Twin>, Box<dyn std::error::Error>> {
+    ) -> Result<AccountData, Error> {
         let storage_address =
-            subxt::dynamic::storage(TFGRID_MODULE, TWINS, vec![Value::u128(id.into())]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
+            subxt::dynamic::storage(SYSTEM_MODULE, ACCOUNT, vec![Value::from_bytes(account)]);
+        Ok(self
+            .fetch_versioned::<V115AccountInfo, V123AccountInfo, _>(&storage_address, block)
             .await?
-            .fetch(&storage_address)
-            .await?;
+            .unwrap_or_default())
+    }
 
-        if result.is_none() {
-            return Ok(None);
-        }
+    /// Get the twin referenced by this ID.
+    async fn twin(&self, id: u32, block: Option<Hash>) -> Result<Option<Twin>, Error> {
+        let storage_address =
+            subxt::dynamic::storage(TFGRID_MODULE, TWINS, vec![Value::u128(id.into())]);
+        self.fetch_versioned::<V115Twin, V123Twin, _>(&storage_address, block)
+            .await
+    }
 
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
+    /// Get the twins referenced by these IDs with a single request, in the order of the IDs.
+    async fn twins_by_ids(
+        &self,
+        ids: &[u32],
+        block: Option<Hash>,
+    ) -> Result<Vec<Option<Twin>>, Error> {
+        let storage_addresses: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                subxt::dynamic::storage(TFGRID_MODULE, TWINS, vec![Value::u128((*id).into())])
+            })
+            .collect();
+        self.fetch_many_versioned::<V115Twin, V123Twin, _>(&storage_addresses, block)
+            .await
+    }
 
-        let twinv115: Result<V115Twin, codec::Error> = codec::decode_from_bytes(r.clone().into());
-        if let Ok(twin) = twinv115 {
-            Ok(Some(twin.into()))
-        } else {
-            let twinv123: V123Twin = codec::decode_from_bytes(r.into())?;
-            Ok(Some(twinv123.into()))
-        }
+    /// Get the ID of the twin bound to this account.
+    async fn twin_id_by_account(
+        &self,
+        account: &AccountId32,
+        block: Option<Hash>,
+    ) -> Result<Option<u32>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            TFGRID_MODULE,
+            TWIN_ID_BY_ACCOUNT_ID,
+            vec![Value::from_bytes(account)],
+        );
+        self.fetch_decoded(&storage_address, block).await
     }
 
-    /// Get the amount of twins on the grid.
-    async fn twin_count(&self, block: Option<Hash>) -> Result<u32, Box<dyn std::error::Error>> {
+    /// Get the ID of the last created twin.
+    async fn twin_count(&self, block: Option<Hash>) -> Result<u32, Error> {
         let storage_address: DynamicStorageAddress<Value> =
             subxt::dynamic::storage(TFGRID_MODULE, TWIN_ID, vec![]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch_or_default(&storage_address)
-            .await?
-            .to_value()?;
+        Ok(self.fetch_number(&storage_address, block).await? as u32)
+    }
 
-        Ok(result.as_u128().map_or(0, |x| x as u32))
+    /// Get the ID of the farm with the given name.
+    async fn farm_id_by_name(&self, name: &str, block: Option<Hash>) -> Result<Option<u32>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            TFGRID_MODULE,
+            FARM_ID_BY_NAME,
+            vec![Value::from_bytes(name)],
+        );
+        self.fetch_decoded(&storage_address, block).await
     }
 
     /// Get the farm referenced by this ID.
-    async fn farm(
-        &self,
-        id: u32,
-        block: Option<Hash>,
-    ) -> Result<Option<Farm>, Box<dyn std::error::Error>> {
+    async fn farm(&self, id: u32, block: Option<Hash>) -> Result<Option<Farm>, Error> {
         let storage_address =
             subxt::dynamic::storage(TFGRID_MODULE, FARMS, vec![Value::u128(id.into())]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch(&storage_address)
-            .await?;
-
-        if result.is_none() {
-            return Ok(None);
-        }
-
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
+        self.fetch_versioned::<V115Farm, V123Farm, _>(&storage_address, block)
+            .await
+    }
 
-        let farmv115: Result<V115Farm, codec::Error> = codec::decode_from_bytes(r.clone().into());
-        if let Ok(farm) = farmv115 {
-            Ok(Some(farm.into()))
-        } else {
-            let farmv123: V123Farm = codec::decode_from_bytes(r.into())?;
-            Ok(Some(farmv123.into()))
-        }
+    /// Get the farms referenced by these IDs with a single request, in the order of the IDs.
+    async fn farms_by_ids(
+        &self,
+        ids: &[u32],
+        block: Option<Hash>,
+    ) -> Result<Vec<Option<Farm>>, Error> {
+        let storage_addresses: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                subxt::dynamic::storage(TFGRID_MODULE, FARMS, vec![Value::u128((*id).into())])
+            })
+            .collect();
+        self.fetch_many_versioned::<V115Farm, V123Farm, _>(&storage_addresses, block)
+            .await
     }
 
     /// Get the payout address of the farm referenced by this ID.
@@ -234,124 +2346,149 @@ impl RuntimeClient for DynamicClient {
         &self,
         id: u32,
         block: Option<Hash>,
-    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    ) -> Result<Option<String>, Error> {
         let storage_address = subxt::dynamic::storage(
             TFGRID_MODULE,
             FARM_PAYOUT_V2_ADDRESS,
             vec![Value::u128(id.into())],
         );
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch(&storage_address)
-            .await?;
-
-        if result.is_none() {
-            return Ok(None);
-        }
-
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
-
-        Ok(Some(codec::decode_from_bytes(r.into())?))
+        self.fetch_decoded(&storage_address, block).await
     }
 
-    /// Get the amount of farms on the grid.
-    async fn farm_count(&self, block: Option<Hash>) -> Result<u32, Box<dyn std::error::Error>> {
+    /// Get the ID of the last created farm.
+    async fn farm_count(&self, block: Option<Hash>) -> Result<u32, Error> {
         let storage_address: DynamicStorageAddress<Value> =
             subxt::dynamic::storage(TFGRID_MODULE, FARM_ID, vec![]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch_or_default(&storage_address)
-            .await?
-            .to_value()?;
-
-        Ok(result.as_u128().map_or(0, |x| x as u32))
+        Ok(self.fetch_number(&storage_address, block).await? as u32)
     }
 
-    /// Get the node referenced by this ID.
-    async fn node(
+    /// Get the ID of the node owned by the twin referenced by this ID.
+    async fn node_id_by_twin_id(
         &self,
-        id: u32,
+        twin_id: u32,
         block: Option<Hash>,
-    ) -> Result<Option<Node>, Box<dyn std::error::Error>> {
+    ) -> Result<Option<u32>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            TFGRID_MODULE,
+            NODE_ID_BY_TWIN_ID,
+            vec![Value::u128(twin_id.into())],
+        );
+        self.fetch_decoded(&storage_address, block).await
+    }
+
+    /// Get the node referenced by this ID.
+    async fn node(&self, id: u32, block: Option<Hash>) -> Result<Option<Node>, Error> {
         let storage_address =
             subxt::dynamic::storage(TFGRID_MODULE, NODES, vec![Value::u128(id.into())]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch(&storage_address)
-            .await?;
+        self.fetch_versioned::<V115Node, V123Node, _>(&storage_address, block)
+            .await
+    }
 
-        if result.is_none() {
-            return Ok(None);
-        }
+    /// Get the nodes referenced by these IDs with a single request, in the order of the IDs.
+    async fn nodes_by_ids(
+        &self,
+        ids: &[u32],
+        block: Option<Hash>,
+    ) -> Result<Vec<Option<Node>>, Error> {
+        let storage_addresses: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                subxt::dynamic::storage(TFGRID_MODULE, NODES, vec![Value::u128((*id).into())])
+            })
+            .collect();
+        self.fetch_many_versioned::<V115Node, V123Node, _>(&storage_addresses, block)
+            .await
+    }
+
+    /// Get the IDs of the nodes in the farm referenced by this ID.
+    async fn farm_nodes(&self, id: u32, block: Option<Hash>) -> Result<Vec<u32>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            TFGRID_MODULE,
+            NODES_BY_FARM_ID,
+            vec![Value::u128(id.into())],
+        );
+        Ok(self
+            .fetch_decoded(&storage_address, block)
+            .await?
+            .unwrap_or_default())
+    }
 
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
+    /// Get the IDs of the active contracts on the node referenced by this ID.
+    async fn node_contracts(&self, id: u32, block: Option<Hash>) -> Result<Vec<u64>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            ACTIVE_NODE_CONTRACTS,
+            vec![Value::u128(id.into())],
+        );
+        Ok(self
+            .fetch_decoded(&storage_address, block)
+            .await?
+            .unwrap_or_default())
+    }
 
-        let nodev115: Result<V115Node, codec::Error> = codec::decode_from_bytes(r.clone().into());
-        if let Ok(node) = nodev115 {
-            Ok(Some(node.into()))
-        } else {
-            let nodev123: V123Node = codec::decode_from_bytes(r.into())?;
-            Ok(Some(nodev123.into()))
-        }
+    /// Get the ID of the active rent contract on the node referenced by this ID.
+    async fn rent_contract_for_node(
+        &self,
+        node_id: u32,
+        block: Option<Hash>,
+    ) -> Result<Option<u64>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            ACTIVE_RENT_CONTRACT_FOR_NODE,
+            vec![Value::u128(node_id.into())],
+        );
+        self.fetch_decoded(&storage_address, block).await
     }
 
-    /// Get the amount of nodes on the grid.
-    async fn node_count(&self, block: Option<Hash>) -> Result<u32, Box<dyn std::error::Error>> {
+    /// Get the ID of the last created node.
+    async fn node_count(&self, block: Option<Hash>) -> Result<u32, Error> {
         let storage_address: DynamicStorageAddress<Value> =
             subxt::dynamic::storage(TFGRID_MODULE, NODE_ID, vec![]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch_or_default(&storage_address)
-            .await?
-            .to_value()?;
+        Ok(self.fetch_number(&storage_address, block).await? as u32)
+    }
 
-        Ok(result.as_u128().map_or(0, |x| x as u32))
+    /// Get the ID of the name contract which registered the given name.
+    async fn contract_id_by_name(
+        &self,
+        name: &str,
+        block: Option<Hash>,
+    ) -> Result<Option<u64>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            CONTRACT_ID_BY_NAME_REGISTRATION,
+            vec![Value::unnamed_composite([bounded_bytes(name.as_bytes())])],
+        );
+        self.fetch_decoded(&storage_address, block).await
     }
 
-    /// Get the contract referenced by this ID.
-    async fn contract(
+    /// Get the ID of the node contract with the given deployment hash on the node referenced by
+    /// this ID.
+    async fn contract_id_by_node_id_and_hash(
         &self,
-        id: u64,
+        node_id: u32,
+        deployment_hash: &[u8; 32],
         block: Option<Hash>,
-    ) -> Result<Option<Contract>, Box<dyn std::error::Error>> {
+    ) -> Result<Option<u64>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            CONTRACT_ID_BY_NODE_ID_AND_HASH,
+            vec![
+                Value::u128(node_id.into()),
+                Value::unnamed_composite(deployment_hash.iter().map(|b| Value::u128((*b).into()))),
+            ],
+        );
+        self.fetch_decoded(&storage_address, block).await
+    }
+
+    /// Get the contract referenced by this ID.
+    async fn contract(&self, id: u64, block: Option<Hash>) -> Result<Option<Contract>, Error> {
         let storage_address = subxt::dynamic::storage(
             SMART_CONTRACT_MODULE,
             CONTRACTS,
             vec![Value::u128(id.into())],
         );
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch(&storage_address)
-            .await?;
-
-        if result.is_none() {
-            return Ok(None);
-        }
-
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
-
-        let cv115: Result<V115Contract, codec::Error> = codec::decode_from_bytes(r.clone().into());
-        if let Ok(contract) = cv115 {
-            Ok(Some(contract.into()))
-        } else {
-            let cv123: V123Contract = codec::decode_from_bytes(r.into())?;
-            Ok(Some(cv123.into()))
-        }
+        self.fetch_versioned::<V115Contract, V123Contract, _>(&storage_address, block)
+            .await
     }
 
     /// Get the resources of the contract referenced by this ID.
@@ -359,50 +2496,75 @@ impl RuntimeClient for DynamicClient {
         &self,
         id: u64,
         block: Option<Hash>,
-    ) -> Result<Option<ContractResources>, Box<dyn std::error::Error>> {
+    ) -> Result<Option<ContractResources>, Error> {
         let storage_address = subxt::dynamic::storage(
             SMART_CONTRACT_MODULE,
             NODE_CONTRACT_RESOURCES,
             vec![Value::u128(id.into())],
         );
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch(&storage_address)
-            .await?;
+        self.fetch_versioned::<V115ContractResources, V123ContractResources, _>(
+            &storage_address,
+            block,
+        )
+        .await
+    }
 
-        if result.is_none() {
-            return Ok(None);
-        }
+    /// Get the billing information of the contract referenced by this ID.
+    async fn contract_billing_information(
+        &self,
+        id: u64,
+        block: Option<Hash>,
+    ) -> Result<Option<ContractBillingInformation>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            CONTRACT_BILLING_INFORMATION_BY_ID,
+            vec![Value::u128(id.into())],
+        );
+        self.fetch_versioned::<V115ContractBillingInformation, V123ContractBillingInformation, _>(
+            &storage_address,
+            block,
+        )
+        .await
+    }
 
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
+    /// Get the funds locked by the contract referenced by this ID.
+    async fn contract_lock(
+        &self,
+        id: u64,
+        block: Option<Hash>,
+    ) -> Result<Option<ContractLock>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            CONTRACT_LOCK,
+            vec![Value::u128(id.into())],
+        );
+        self.fetch_versioned::<V115ContractLock, V123ContractLock, _>(&storage_address, block)
+            .await
+    }
 
-        let crv115: Result<V115ContractResources, codec::Error> =
-            codec::decode_from_bytes(r.clone().into());
-        if let Ok(resources) = crv115 {
-            Ok(Some(resources.into()))
-        } else {
-            let crv123: V123ContractResources = codec::decode_from_bytes(r.into())?;
-            Ok(Some(crv123.into()))
-        }
+    /// Get the solution provider referenced by this ID.
+    async fn solution_provider(
+        &self,
+        id: u64,
+        block: Option<Hash>,
+    ) -> Result<Option<SolutionProvider>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            SMART_CONTRACT_MODULE,
+            SOLUTION_PROVIDERS,
+            vec![Value::u128(id.into())],
+        );
+        self.fetch_versioned::<V115SolutionProvider, V123SolutionProvider, _>(
+            &storage_address,
+            block,
+        )
+        .await
     }
 
-    /// Get the amount of contracts on the grid.
-    async fn contract_count(&self, block: Option<Hash>) -> Result<u64, Box<dyn std::error::Error>> {
+    /// Get the ID of the last created contract.
+    async fn contract_count(&self, block: Option<Hash>) -> Result<u64, Error> {
         let storage_address: DynamicStorageAddress<Value> =
             subxt::dynamic::storage(SMART_CONTRACT_MODULE, CONTRACT_ID, vec![]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch_or_default(&storage_address)
-            .await?
-            .to_value()?;
-
-        Ok(result.as_u128().map_or(0, |x| x as u64))
+        Ok(self.fetch_number(&storage_address, block).await? as u64)
     }
 
     /// Get the farming policy referenced by this ID.
@@ -410,52 +2572,591 @@ impl RuntimeClient for DynamicClient {
         &self,
         id: u32,
         block: Option<Hash>,
-    ) -> Result<Option<FarmPolicy>, Box<dyn std::error::Error>> {
+    ) -> Result<Option<FarmPolicy>, Error> {
         let storage_address = subxt::dynamic::storage(
             TFGRID_MODULE,
             FARMING_POLICIES,
             vec![Value::u128(id.into())],
         );
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch(&storage_address)
-            .await?;
+        self.fetch_versioned::<V115FarmingPolicy, V123FarmingPolicy, _>(&storage_address, block)
+            .await
+    }
+
+    /// Get the ID of the last created farming policy.
+    async fn farming_policy_count(&self, block: Option<Hash>) -> Result<u32, Error> {
+        let storage_address: DynamicStorageAddress<Value> =
+            subxt::dynamic::storage(TFGRID_MODULE, FARMING_POLICY_ID, vec![]);
+        Ok(self.fetch_number(&storage_address, block).await? as u32)
+    }
+
+    /// Get the pricing policy referenced by this ID.
+    async fn pricing_policy(
+        &self,
+        id: u32,
+        block: Option<Hash>,
+    ) -> Result<Option<PricingPolicy>, Error> {
+        let storage_address = subxt::dynamic::storage(
+            TFGRID_MODULE,
+            PRICING_POLICIES,
+            vec![Value::u128(id.into())],
+        );
+        self.fetch_versioned::<V115PricingPolicy, V123PricingPolicy, _>(&storage_address, block)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value as Json};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// A node which serves JSON-RPC over HTTP, with the metadata of runtime 123. Other requests are
+    /// passed to `respond`, and requests for which it returns `None` are never answered.
+    struct FakeNode {
+        url: String,
+        methods: Arc<std::sync::Mutex<Vec<String>>>,
+    }
 
-        if result.is_none() {
-            return Ok(None);
+    impl FakeNode {
+        async fn start(respond: impl Fn(&Json) -> Option<Json> + Send + Sync + 'static) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let methods = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let respond = Arc::new(respond);
+            let node_methods = methods.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    let (respond, methods) = (respond.clone(), node_methods.clone());
+                    tokio::spawn(async move {
+                        let (reader, mut writer) = stream.into_split();
+                        let mut reader = BufReader::new(reader);
+                        while let Some(request) = read_request(&mut reader).await {
+                            let method = request["method"].as_str().unwrap().to_string();
+                            methods.lock().unwrap().push(method.clone());
+                            let Some(result) =
+                                setup_response(&method).or_else(|| respond(&request))
+                            else {
+                                return std::future::pending().await;
+                            };
+                            let body =
+                                json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+                                    .to_string();
+                            let response = format!(
+                                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                                body.len(),
+                                body
+                            );
+                            writer.write_all(response.as_bytes()).await.unwrap();
+                        }
+                    });
+                }
+            });
+            FakeNode { url, methods }
         }
 
-        let r: Vec<u8> = result.unwrap().into_encoded().into();
+        /// The amount of requests for the method the node received.
+        fn requests(&self, method: &str) -> usize {
+            let methods = self.methods.lock().unwrap();
+            methods.iter().filter(|m| *m == method).count()
+        }
+    }
 
-        let fpv115: Result<V115FarmingPolicy, codec::Error> =
-            codec::decode_from_bytes(r.clone().into());
-        if let Ok(policy) = fpv115 {
-            Ok(Some(policy.into()))
-        } else {
-            let fpv123: V123FarmingPolicy = codec::decode_from_bytes(r.into())?;
-            Ok(Some(fpv123.into()))
+    /// Read the body of the next HTTP request on the connection.
+    async fn read_request(reader: &mut BufReader<tokio::net::tcp::OwnedReadHalf>) -> Option<Json> {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await.ok()? == 0 {
+                return None;
+            }
+            let line = line.trim_end().to_ascii_lowercase();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(length) = line.strip_prefix("content-length:") {
+                content_length = length.trim().parse().ok()?;
+            }
         }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await.ok()?;
+        serde_json::from_slice(&body).ok()
     }
 
-    /// Get the amount of farming policies on the grid.
-    async fn farming_policy_count(
-        &self,
-        block: Option<Hash>,
-    ) -> Result<u32, Box<dyn std::error::Error>> {
-        let storage_address: DynamicStorageAddress<Value> =
-            subxt::dynamic::storage(TFGRID_MODULE, FARMING_POLICY_ID, vec![]);
-        let result = self
-            .api
-            .storage()
-            .at(block)
-            .await?
-            .fetch_or_default(&storage_address)
-            .await?
-            .to_value()?;
+    /// The responses to the requests a client makes when it connects.
+    fn setup_response(method: &str) -> Option<Json> {
+        match method {
+            "chain_getBlockHash" => Some(json!(format!("0x{}", "00".repeat(32)))),
+            "state_getRuntimeVersion" => Some(json!({
+                "specName": "substrate-threefold",
+                "specVersion": 123,
+                "transactionVersion": 1,
+            })),
+            "state_call" => {
+                let metadata = include_bytes!("../artifacts/v123.scale").to_vec();
+                Some(json!(hex(&metadata.encode())))
+            }
+            _ => None,
+        }
+    }
+
+    /// Encode the bytes as hex with a `0x` prefix, as JSON-RPC expects.
+    fn hex(bytes: &[u8]) -> String {
+        let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        format!("0x{hex}")
+    }
+
+    #[test]
+    fn transient_errors() {
+        let client_error =
+            |err: JsonRpseeError| subxt::Error::Rpc(RpcError::ClientError(Box::new(err)));
+        let io_error = || std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+
+        assert!(is_transient(&subxt::Error::Io(io_error())));
+        assert!(is_transient(&subxt::Error::Rpc(
+            RpcError::SubscriptionDropped
+        )));
+        assert!(is_transient(&client_error(JsonRpseeError::Transport(
+            io_error().into()
+        ))));
+        assert!(is_transient(&client_error(JsonRpseeError::RestartNeeded(
+            "connection closed".to_string()
+        ))));
+        assert!(is_transient(&client_error(JsonRpseeError::RequestTimeout)));
+        assert!(is_transient(&subxt::Error::Rpc(RpcError::ClientError(
+            Box::new(io_error())
+        ))));
+
+        assert!(!is_transient(&client_error(JsonRpseeError::Custom(
+            "invalid params".to_string()
+        ))));
+        assert!(!is_transient(&subxt::Error::Rpc(RpcError::ClientError(
+            "invalid params".into()
+        ))));
+        assert!(!is_transient(&subxt::Error::Other(
+            "decoding failed".into()
+        )));
+    }
+
+    #[test]
+    fn public_ips_need_a_subnet() {
+        assert!(validate_ip4_subnet("185.206.122.33/24").is_ok());
+        assert!(validate_ip4_subnet("10.0.0.1/32").is_ok());
+        assert!(validate_ip4_subnet("0.0.0.0/0").is_ok());
+
+        assert!(matches!(
+            validate_ip4_subnet("185.206.122.33"),
+            Err(Error::InvalidPublicIp(ip)) if ip == "185.206.122.33"
+        ));
+        assert!(validate_ip4_subnet("185.206.122.33/33").is_err());
+        assert!(validate_ip4_subnet("185.206.122.33/").is_err());
+        assert!(validate_ip4_subnet("185.206.122/24").is_err());
+        assert!(validate_ip4_subnet("2a02:1802:5e::1/64").is_err());
+    }
+
+    fn runtime_metadata(encoded: &[u8]) -> Metadata {
+        use subxt::ext::frame_metadata::RuntimeMetadataPrefixed;
+
+        RuntimeMetadataPrefixed::decode(&mut &*encoded)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn node_registration() -> NodeRegistration {
+        NodeRegistration {
+            farm_id: 1,
+            resources: Resources {
+                hru: 4,
+                sru: 3,
+                cru: 2,
+                mru: 1,
+            },
+            location: crate::types::Location {
+                longitude: "4.35".to_string(),
+                latitude: "50.85".to_string(),
+            },
+            country: "Belgium".to_string(),
+            city: "Brussels".to_string(),
+            interfaces: vec![],
+            secure_boot: false,
+            virtualized: true,
+            serial_number: None,
+        }
+    }
+
+    #[test]
+    fn batch_all_is_encoded_in_order() {
+        use crate::runtimes::v123::runtime::api::{
+            runtime_types::{
+                pallet_smart_contract::pallet::Call as ContractCall,
+                pallet_tfgrid::pallet::Call as TfgridCall, tfchain_runtime::RuntimeCall,
+            },
+            utility::calls::BatchAll,
+        };
+        use subxt::tx::TxPayload;
+
+        let metadata = runtime_metadata(include_bytes!("../artifacts/v123.scale"));
+        let calls = [
+            Call::ReportUptime { uptime: 3600 },
+            Call::CancelContract { contract_id: 7 },
+        ];
+        let encoded = batch_all_payload(&calls, &metadata)
+            .encode_call_data(&metadata)
+            .unwrap();
+
+        let decoded = BatchAll::decode(&mut &encoded[2..]).unwrap();
+        assert!(matches!(
+            decoded.calls.as_slice(),
+            [
+                RuntimeCall::TfgridModule(TfgridCall::report_uptime { uptime: 3600 }),
+                RuntimeCall::SmartContractModule(ContractCall::cancel_contract { contract_id: 7 }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn create_node_is_encoded_for_runtime_115() {
+        use crate::runtimes::v115::runtime::api::tfgrid_module::calls::CreateNode;
+        use subxt::tx::TxPayload;
+
+        let metadata = runtime_metadata(include_bytes!("../artifacts/v115.scale"));
+        let call = Call::CreateNode(node_registration());
+        let encoded = call_payload(&call, &metadata)
+            .encode_call_data(&metadata)
+            .unwrap();
+
+        // Skip the indices of the pallet and the call.
+        let decoded = CreateNode::decode(&mut &encoded[2..]).unwrap();
+        assert_eq!(decoded.country, b"Belgium");
+        assert_eq!(decoded.city, b"Brussels");
+        assert_eq!(decoded.location.latitude, b"50.85");
+        assert!(decoded.serial_number.is_empty());
+    }
+
+    #[test]
+    fn create_node_is_encoded_for_runtime_123() {
+        use crate::runtimes::v123::runtime::api::tfgrid_module::calls::CreateNode;
+        use subxt::tx::TxPayload;
+
+        let metadata = runtime_metadata(include_bytes!("../artifacts/v123.scale"));
+        let call = Call::CreateNode(node_registration());
+        let encoded = call_payload(&call, &metadata)
+            .encode_call_data(&metadata)
+            .unwrap();
+
+        let decoded = CreateNode::decode(&mut &encoded[2..]).unwrap();
+        assert_eq!(decoded.location.country.0, b"Belgium");
+        assert_eq!(decoded.location.city.0, b"Brussels");
+        assert_eq!(decoded.location.latitude.0, b"50.85");
+        assert!(decoded.serial_number.is_none());
+    }
+
+    #[tokio::test]
+    async fn retry_reconnects_to_the_only_endpoint() {
+        let calls = AtomicUsize::new(0);
+        let node = FakeNode::start(move |request| match request["method"].as_str().unwrap() {
+            // The first call hangs, like on a half open connection.
+            "system_chain" if calls.fetch_add(1, Ordering::Relaxed) == 0 => None,
+            "system_chain" => Some(json!("tfchain")),
+            _ => None,
+        })
+        .await;
+        let client = DynamicClient::new(&node.url)
+            .await
+            .unwrap()
+            .with_call_timeout(Duration::from_millis(200));
+
+        assert_eq!(client.chain_name().await.unwrap(), "tfchain");
+        assert_eq!(node.requests("system_chain"), 2);
+        // The metadata is fetched once per connection.
+        assert_eq!(node.requests("state_call"), 2);
+        assert_eq!(client.endpoint(), node.url);
+    }
+
+    #[tokio::test]
+    async fn concurrent_failures_fail_over_once() {
+        let calls = AtomicUsize::new(0);
+        let node = FakeNode::start(move |request| match request["method"].as_str().unwrap() {
+            // The calls of both clones hang.
+            "system_chain" if calls.fetch_add(1, Ordering::Relaxed) < 2 => None,
+            "system_chain" => Some(json!("tfchain")),
+            _ => None,
+        })
+        .await;
+        let client = DynamicClient::new(&node.url)
+            .await
+            .unwrap()
+            .with_call_timeout(Duration::from_millis(200));
+
+        let other = client.clone();
+        let (chain, other_chain) = futures::join!(client.chain_name(), other.chain_name());
+        assert_eq!(chain.unwrap(), "tfchain");
+        assert_eq!(other_chain.unwrap(), "tfchain");
+        // The metadata is fetched once for the initial connection, and once when failing over.
+        assert_eq!(node.requests("state_call"), 2);
+    }
+
+    #[tokio::test]
+    async fn failover_skips_a_hanging_endpoint() {
+        let calls = AtomicUsize::new(0);
+        let node = FakeNode::start(move |request| match request["method"].as_str().unwrap() {
+            "system_chain" if calls.fetch_add(1, Ordering::Relaxed) == 0 => None,
+            "system_chain" => Some(json!("tfchain")),
+            _ => None,
+        })
+        .await;
+        // Accepts connections, but never answers.
+        let hanging = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hanging_url = format!("http://{}", hanging.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            loop {
+                connections.push(hanging.accept().await.unwrap());
+            }
+        });
+        let client = DynamicClient::with_endpoints(&[&node.url, &hanging_url])
+            .await
+            .unwrap()
+            .with_call_timeout(Duration::from_millis(200));
+
+        let chain = tokio::time::timeout(Duration::from_secs(5), client.chain_name()).await;
+        assert_eq!(chain.unwrap().unwrap(), "tfchain");
+        assert_eq!(client.endpoint(), node.url);
+    }
+
+    /// Start a node which answers batched queries of the storage map `TfgridModule::<item>` with
+    /// the encoded values by ID.
+    async fn storage_node(item: &'static str, values: Vec<(u32, Vec<u8>)>) -> FakeNode {
+        let metadata = runtime_metadata(include_bytes!("../artifacts/v123.scale"));
+        let values: HashMap<String, String> = values
+            .into_iter()
+            .map(|(id, value)| {
+                let address =
+                    subxt::dynamic::storage(TFGRID_MODULE, item, vec![Value::u128(id.into())]);
+                let key = storage_address_bytes(&address, &metadata).unwrap();
+                (hex(&key), hex(&value))
+            })
+            .collect();
+        FakeNode::start(move |request| match request["method"].as_str().unwrap() {
+            "state_queryStorageAt" => {
+                let changes: Vec<Json> = request["params"][0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|key| json!([key, values.get(key.as_str().unwrap())]))
+                    .collect();
+                Some(json!([{ "block": hex(&[0; 32]), "changes": changes }]))
+            }
+            _ => None,
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn twins_and_farms_by_ids_are_in_the_order_of_the_ids() {
+        use crate::runtimes::v123::runtime::api::runtime_types::{
+            pallet_tfgrid::{farm::FarmName, twin::TwinIp},
+            sp_core::bounded::bounded_vec::BoundedVec,
+            tfchain_support::types::{Farm as RuntimeFarm, FarmCertification},
+        };
+        use crate::runtimes::v123::types::{V123Farm, V123Twin};
+
+        let twin = |id: u32| V123Twin {
+            version: 1,
+            id,
+            account_id: AccountId32([id as u8; 32]),
+            ip: TwinIp(BoundedVec(b"::1".to_vec())),
+            entities: vec![],
+        };
+        let farm = |id: u32| -> V123Farm {
+            RuntimeFarm {
+                version: 1,
+                id,
+                name: FarmName(BoundedVec(format!("farm{id}").into_bytes())),
+                twin_id: 1,
+                pricing_policy_id: 1,
+                certification: FarmCertification::NotCertified,
+                public_ips: BoundedVec(vec![]),
+                dedicated_farm: false,
+                farming_policy_limits: None,
+            }
+        };
+
+        let node = storage_node(TWINS, vec![(1, twin(1).encode()), (3, twin(3).encode())]).await;
+        let client = DynamicClient::new(&node.url).await.unwrap();
+        let twins = client.twins_by_ids(&[3, 2, 1, 3], None).await.unwrap();
+        let ids: Vec<_> = twins.iter().map(|t| t.as_ref().map(|t| t.id)).collect();
+        assert_eq!(ids, [Some(3), None, Some(1), Some(3)]);
+
+        let node = storage_node(FARMS, vec![(2, farm(2).encode())]).await;
+        let client = DynamicClient::new(&node.url).await.unwrap();
+        let farms = client.farms_by_ids(&[1, 2, 2], None).await.unwrap();
+        let names: Vec<_> = farms.iter().map(|f| f.as_ref().map(|f| &*f.name)).collect();
+        assert_eq!(names, [None, Some("farm2"), Some("farm2")]);
+    }
+
+    #[tokio::test]
+    async fn fetch_many_versioned_returns_a_value_per_address() {
+        // The value with ID 2 doesn't exist.
+        let node = storage_node(NODES, vec![(1, 10u32.encode()), (3, 30u32.encode())]).await;
+        let client = DynamicClient::new(&node.url).await.unwrap();
+
+        let address =
+            |id: u32| subxt::dynamic::storage(TFGRID_MODULE, NODES, vec![Value::u128(id.into())]);
+        let addresses = [address(1), address(2), address(1), address(3)];
+        let fetched = client
+            .fetch_many_versioned::<u32, u32, u32>(&addresses, None)
+            .await
+            .unwrap();
+        assert_eq!(fetched, [Some(10), None, Some(10), Some(30)]);
+        assert_eq!(node.requests("state_queryStorageAt"), 1);
+    }
+
+    fn header(number: u32) -> Header {
+        Header {
+            parent_hash: Hash::zero(),
+            number,
+            state_root: Hash::zero(),
+            extrinsics_root: Hash::zero(),
+            digest: Default::default(),
+        }
+    }
+
+    /// The heights of the blocks a backfill yielded, or `None` for errors.
+    async fn backfilled(
+        stream: BoxStream<'static, Result<BlockEvents, Error>>,
+    ) -> Vec<Option<u32>> {
+        stream
+            .map(|item| item.ok().map(|events| events.number))
+            .collect()
+            .await
+    }
+
+    fn events_at(number: u32) -> Result<BlockEvents, Error> {
+        Ok(BlockEvents {
+            number,
+            hash: Hash::zero(),
+            events: vec![],
+        })
+    }
+
+    #[tokio::test]
+    async fn backfill_follows_heads_without_gaps() {
+        // Heads can be skipped, or arrive after a later head.
+        let heads = futures::stream::iter([Ok(header(12)), Ok(header(11)), Ok(header(14))]);
+        let fetch = |height| async move { events_at(height) };
+        let stream = backfill(heads, 10, 11, RetryPolicy::default(), fetch);
+
+        assert_eq!(
+            backfilled(stream).await,
+            [Some(10), Some(11), Some(12), Some(13), Some(14)]
+        );
+    }
+
+    #[tokio::test]
+    async fn backfill_fetches_a_failed_block_again_after_the_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(20),
+            backoff_multiplier: 2.0,
+            max_delay: Duration::MAX,
+        };
+        let failures = Arc::new(AtomicUsize::new(0));
+        let fetch = move |height| {
+            let failures = failures.clone();
+            async move {
+                if height == 11 && failures.fetch_add(1, Ordering::Relaxed) < 2 {
+                    return Err(Error::Other("unavailable".to_string()));
+                }
+                events_at(height)
+            }
+        };
+        let stream = backfill(futures::stream::empty(), 10, 11, policy, fetch);
+
+        let start = std::time::Instant::now();
+        assert_eq!(backfilled(stream).await, [Some(10), None, None, Some(11)]);
+        assert!(start.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[tokio::test]
+    async fn backfill_ends_after_repeated_failures() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            ..RetryPolicy::default()
+        };
+        let fetch = |_| async { Err(Error::Other("unavailable".to_string())) };
+        let heads = futures::stream::pending();
+        let stream = backfill(heads, 10, 11, policy, fetch);
+
+        assert_eq!(backfilled(stream).await, [None, None, None]);
+    }
+
+    #[test]
+    fn retry_delay_backs_off_up_to_max_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            backoff_multiplier: 3.0,
+            max_delay: Duration::from_secs(1),
+        };
+        let delays: Vec<_> = std::iter::successors(Some(policy.first_delay()), |delay| {
+            Some(policy.next_delay(*delay))
+        })
+        .take(4)
+        .collect();
+        assert_eq!(
+            delays,
+            [
+                Duration::from_millis(100),
+                Duration::from_millis(300),
+                Duration::from_millis(900),
+                Duration::from_secs(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn retry_delay_is_not_capped_by_default() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_secs(2),
+            ..Default::default()
+        };
+        assert_eq!(policy.first_delay(), Duration::from_secs(2));
+        assert_eq!(
+            policy.next_delay(Duration::from_secs(2)),
+            Duration::from_secs(2)
+        );
+
+        let policy = RetryPolicy {
+            backoff_multiplier: 2.0,
+            ..policy
+        };
+        assert_eq!(policy.next_delay(Duration::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn retry_delay_backs_off_by_a_fraction() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_millis(100),
+            backoff_multiplier: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.next_delay(policy.first_delay()),
+            Duration::from_millis(150)
+        );
+    }
 
-        Ok(result.as_u128().map_or(0, |x| x as u32))
+    #[test]
+    fn first_retry_delay_is_capped() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(1),
+            ..Default::default()
+        };
+        assert_eq!(policy.first_delay(), Duration::from_secs(1));
     }
 }
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use subxt::error::{DispatchError, MetadataError};
+
+use crate::client::TimestampError;
+use crate::types::Hash;
+
+/// Errors returned by the clients, with context about the operation which failed.
+#[derive(Debug)]
+pub enum Error {
+    /// A request to the node failed.
+    Rpc(subxt::Error),
+    /// A pallet or storage item is not known in the metadata of the runtime.
+    Metadata(MetadataError),
+    /// The value of a storage item could not be decoded.
+    StorageDecode {
+        pallet: String,
+        item: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Data which is not read from storage, e.g. a raw event blob, could not be decoded.
+    Decode(codec::Error),
+    /// An extrinsic could not be signed because the client has no signer configured, see
+    /// [`DynamicClient::with_signer`](crate::dynamic::DynamicClient::with_signer).
+    NoSigner,
+    /// An extrinsic was included in a block, but failed to dispatch. Module errors are resolved to
+    /// the name of the pallet and error using the metadata.
+    Dispatch {
+        extrinsic_hash: Hash,
+        block_hash: Hash,
+        error: Box<DispatchError>,
+    },
+    /// Searching the height of the chain at a timestamp failed.
+    Timestamp(TimestampError),
+    /// A public IP is not an IPv4 address with the prefix length of its subnet.
+    InvalidPublicIp(String),
+    /// Any other failure, e.g. invalid input or an unexpected response of the node.
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Rpc(err) => write!(f, "request to the node failed: {}", err),
+            Error::Metadata(err) => write!(f, "invalid metadata lookup: {}", err),
+            Error::StorageDecode {
+                pallet,
+                item,
+                source,
+            } => write!(f, "could not decode {}::{}: {}", pallet, item, source),
+            Error::Decode(err) => write!(f, "could not decode data: {}", err),
+            Error::NoSigner => f.write_str("no signer configured"),
+            Error::Dispatch {
+                extrinsic_hash,
+                block_hash,
+                error,
+            } => write!(
+                f,
+                "extrinsic {:?} in block {:?} failed: {}",
+                extrinsic_hash, block_hash, error
+            ),
+            Error::Timestamp(err) => err.fmt(f),
+            Error::InvalidPublicIp(ip) => write!(
+                f,
+                "invalid public IP {}, expected an IPv4 address with a prefix length, e.g. 185.206.122.33/24",
+                ip
+            ),
+            Error::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Rpc(err) => Some(err),
+            Error::Metadata(err) => Some(err),
+            Error::StorageDecode { source, .. } => Some(source.as_ref()),
+            Error::Decode(err) => Some(err),
+            Error::NoSigner => None,
+            Error::Dispatch { error, .. } => Some(error.as_ref()),
+            Error::Timestamp(err) => Some(err),
+            Error::InvalidPublicIp(_) => None,
+            Error::Other(_) => None,
+        }
+    }
+}
+
+impl From<subxt::Error> for Error {
+    fn from(err: subxt::Error) -> Self {
+        Error::Rpc(err)
+    }
+}
+
+impl From<MetadataError> for Error {
+    fn from(err: MetadataError) -> Self {
+        Error::Metadata(err)
+    }
+}
+
+impl From<codec::Error> for Error {
+    fn from(err: codec::Error) -> Self {
+        Error::Decode(err)
+    }
+}
+
+impl From<TimestampError> for Error {
+    fn from(err: TimestampError) -> Self {
+        Error::Timestamp(err)
+    }
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::Other(msg)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(msg: &str) -> Self {
+        Error::Other(msg.to_string())
+    }
+}
@@ -1 +1,122 @@
-pub enum TfchainEvent {}
+use codec::DecodeAll;
+
+use crate::runtimes::{
+    v115::types::{self as v115, V115EventRecords},
+    v123::types::{self as v123, V123EventRecords},
+};
+use crate::types::{DecodedEvent, RuntimeEvents};
+
+/// Decode the SCALE encoded events of a block, i.e. the value of the `System::Events` storage
+/// item, keeping only the events which are one of the [`RuntimeEvents`]. Unlike the clients, this
+/// doesn't need a connection to a node, but it can only decode events of the runtimes for which
+/// the crate has the types.
+pub fn decode_events(raw: &[u8]) -> Result<Vec<RuntimeEvents>, codec::Error> {
+    Ok(decode_event_records(raw)?
+        .into_iter()
+        .map(|record| record.event)
+        .collect())
+}
+
+/// Decode the SCALE encoded events of a block like [`decode_events`], but keep the phase in which
+/// every event was emitted and its topics, e.g. to attribute events to the extrinsic which caused
+/// them.
+pub fn decode_event_records(raw: &[u8]) -> Result<Vec<DecodedEvent>, codec::Error> {
+    if let Ok(records) = V115EventRecords::decode_all(&mut &*raw) {
+        return Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                Some(DecodedEvent {
+                    phase: record.phase.into(),
+                    event: v115::known_event(record.event)?,
+                    topics: record.topics,
+                })
+            })
+            .collect());
+    }
+    let records = V123EventRecords::decode_all(&mut &*raw)?;
+    Ok(records
+        .into_iter()
+        .filter_map(|record| {
+            Some(DecodedEvent {
+                phase: record.phase.into(),
+                event: v123::known_event(record.event)?,
+                topics: record.topics,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::Encode;
+
+    use super::*;
+    use crate::runtimes::v123::runtime::api::{
+        self,
+        runtime_types::{frame_system::EventRecord, frame_system::Phase as RuntimePhase},
+    };
+    use crate::types::Phase;
+
+    fn record(
+        phase: RuntimePhase,
+        event: api::Event,
+    ) -> EventRecord<api::Event, subxt::utils::H256> {
+        EventRecord {
+            phase,
+            event,
+            topics: vec![],
+        }
+    }
+
+    fn encoded_records() -> Vec<u8> {
+        let records: V123EventRecords = vec![
+            record(
+                RuntimePhase::Initialization,
+                api::Event::Balances(api::balances::Event::Endowed {
+                    account: subxt::utils::AccountId32([1; 32]),
+                    free_balance: 10,
+                }),
+            ),
+            record(
+                RuntimePhase::ApplyExtrinsic(2),
+                api::Event::TfgridModule(api::tfgrid_module::Event::NodeUptimeReported(
+                    7, 1000, 60,
+                )),
+            ),
+        ];
+        records.encode()
+    }
+
+    #[test]
+    fn no_events() {
+        let raw = V123EventRecords::new().encode();
+        assert!(decode_events(&raw).unwrap().is_empty());
+        assert!(decode_event_records(&raw).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_known_events_are_decoded() {
+        let events = decode_events(&encoded_records()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            RuntimeEvents::NodeUptimeReported(7, 1000, 60)
+        ));
+    }
+
+    #[test]
+    fn event_records_keep_the_phase() {
+        let records = decode_event_records(&encoded_records()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].phase, Phase::ApplyExtrinsic(2));
+        assert!(records[0].topics.is_empty());
+    }
+
+    #[test]
+    fn invalid_events() {
+        let mut raw = encoded_records();
+        raw.push(0);
+        assert!(decode_events(&raw).is_err());
+        assert!(decode_events(&[0xff]).is_err());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod client;
 pub mod dynamic;
+pub mod error;
 pub mod events;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
 pub mod runtimes;
 pub mod types;
 
@@ -5,7 +5,7 @@ use tfchain_client::dynamic;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dyn_cl = dynamic::DynamicClient::new("wss://tfchain.grid.tf:443").await?;
 
-    // let block_before_upgrade = dyn_cl.hash_at_height(Some(5710579 as u32)).await?;
+    // let block_before_upgrade = dyn_cl.hash_at_height(Some(5710579)).await?;
     // let time = dyn_cl.timestamp(block_before_upgrade).await?;
     // print!("time: {}", time);
     // let node = dyn_cl.node(1, block_before_upgrade).await?;
@@ -19,7 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     //     println!("node after upgrade found: {:?}", node);
     // }
 
-    let bl = dyn_cl.hash_at_height(Some(5710579 as u32)).await?;
+    let bl = dyn_cl.hash_at_height(Some(5710579)).await?;
     let events = dyn_cl.events(bl).await?;
     for e in events.iter() {
         println!("events: {:?}", e);
@@ -0,0 +1,451 @@
+//! An in memory [`RuntimeClient`], to test code built on top of the client without a node.
+
+use std::collections::HashMap;
+use subxt::utils::AccountId32;
+
+use crate::client::RuntimeClient;
+use crate::error::Error;
+use crate::types::{
+    AccountData, Block, Contract, ContractBillingInformation, ContractData, ContractLock,
+    ContractResources, ContractState, Farm, FarmCertification, FarmPolicy, Hash, Location, Node,
+    NodeCertification, PricingPolicy, Resources, RuntimeEvents, SolutionProvider, Twin,
+};
+
+/// A [`RuntimeClient`] which returns the values set on it, rather than fetching them from a node.
+/// The `block` argument of the methods is ignored, all values are returned regardless of the
+/// block they are requested at, except for timestamps set with [`MockClient::set_timestamp_at`].
+/// Values which are not set are reported as not existing on chain.
+///
+/// # Example
+///
+/// ```
+/// use tfchain_client::client::RuntimeClient;
+/// use tfchain_client::mock::MockClient;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut client = MockClient::new();
+/// client.set_latest_block_number(100);
+///
+/// assert_eq!(client.latest_block_number().await.unwrap(), 100);
+/// assert!(client.node(1, None).await.unwrap().is_none());
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    latest_block_number: u32,
+    latest_finalized_hash: Hash,
+    timestamp: u64,
+    timestamps: HashMap<Hash, u64>,
+    hashes: HashMap<u32, Hash>,
+    events: Vec<RuntimeEvents>,
+    accounts: HashMap<[u8; 32], AccountData>,
+    twins: HashMap<u32, Twin>,
+    farms: HashMap<u32, Farm>,
+    farm_payout_addresses: HashMap<u32, String>,
+    nodes: HashMap<u32, Node>,
+    contracts: HashMap<u64, Contract>,
+    contract_resources: HashMap<u64, ContractResources>,
+    contract_billing_information: HashMap<u64, ContractBillingInformation>,
+    contract_locks: HashMap<u64, ContractLock>,
+    solution_providers: HashMap<u64, SolutionProvider>,
+    farming_policies: HashMap<u32, FarmPolicy>,
+    pricing_policies: HashMap<u32, PricingPolicy>,
+}
+
+impl MockClient {
+    /// Create a client without any values.
+    pub fn new() -> MockClient {
+        MockClient::default()
+    }
+
+    /// Set the height of the latest finalized block.
+    pub fn set_latest_block_number(&mut self, height: u32) {
+        self.latest_block_number = height;
+    }
+
+    /// Set the hash of the latest finalized block.
+    pub fn set_latest_finalized_hash(&mut self, hash: Hash) {
+        self.latest_finalized_hash = hash;
+    }
+
+    /// Set the hash of the block at the given height.
+    pub fn set_hash_at_height(&mut self, height: u32, hash: Hash) {
+        self.hashes.insert(height, hash);
+    }
+
+    /// Set the on chain timestamp, in seconds since the UNIX epoch.
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = timestamp;
+    }
+
+    /// Set the on chain timestamp of the block with the given hash, in seconds since the UNIX
+    /// epoch. Blocks without a timestamp of their own use the one set with
+    /// [`MockClient::set_timestamp`].
+    pub fn set_timestamp_at(&mut self, hash: Hash, timestamp: u64) {
+        self.timestamps.insert(hash, timestamp);
+    }
+
+    /// Set the events returned for every block.
+    pub fn set_events(&mut self, events: Vec<RuntimeEvents>) {
+        self.events = events;
+    }
+
+    /// Set the balances of the account.
+    pub fn set_account_data(&mut self, account: AccountId32, data: AccountData) {
+        self.accounts.insert(account.0, data);
+    }
+
+    /// Set a twin, replacing any twin with the same ID.
+    pub fn set_twin(&mut self, twin: Twin) {
+        self.twins.insert(twin.id, twin);
+    }
+
+    /// Set a farm, replacing any farm with the same ID.
+    pub fn set_farm(&mut self, farm: Farm) {
+        self.farms.insert(farm.id, farm);
+    }
+
+    /// Set the payout address of the farm referenced by this ID.
+    pub fn set_farm_payout_address(&mut self, farm_id: u32, address: String) {
+        self.farm_payout_addresses.insert(farm_id, address);
+    }
+
+    /// Set a node, replacing any node with the same ID.
+    pub fn set_node(&mut self, node: Node) {
+        self.nodes.insert(node.id, node);
+    }
+
+    /// Set a contract, replacing any contract with the same ID.
+    pub fn set_contract(&mut self, contract: Contract) {
+        self.contracts.insert(contract.contract_id, contract);
+    }
+
+    /// Set the resources of a contract.
+    pub fn set_contract_resources(&mut self, resources: ContractResources) {
+        self.contract_resources
+            .insert(resources.contract_id, resources);
+    }
+
+    /// Set the billing information of the contract referenced by this ID.
+    pub fn set_contract_billing_information(
+        &mut self,
+        contract_id: u64,
+        info: ContractBillingInformation,
+    ) {
+        self.contract_billing_information.insert(contract_id, info);
+    }
+
+    /// Set the funds locked by the contract referenced by this ID.
+    pub fn set_contract_lock(&mut self, contract_id: u64, lock: ContractLock) {
+        self.contract_locks.insert(contract_id, lock);
+    }
+
+    /// Set a solution provider, replacing any solution provider with the same ID.
+    pub fn set_solution_provider(&mut self, provider: SolutionProvider) {
+        self.solution_providers
+            .insert(provider.solution_provider_id, provider);
+    }
+
+    /// Set a farming policy, replacing any farming policy with the same ID.
+    pub fn set_farming_policy(&mut self, policy: FarmPolicy) {
+        self.farming_policies.insert(policy.id, policy);
+    }
+
+    /// Set a pricing policy, replacing any pricing policy with the same ID.
+    pub fn set_pricing_policy(&mut self, policy: PricingPolicy) {
+        self.pricing_policies.insert(policy.id, policy);
+    }
+}
+
+/// A twin with the given ID, bound to an account whose bytes are all the lowest byte of the ID. The
+/// fields can be changed before passing it to [`MockClient::set_twin`].
+pub fn twin(id: u32) -> Twin {
+    Twin {
+        version: 1,
+        id,
+        account_id: AccountId32([id as u8; 32]),
+        ip: "::1".parse().unwrap(),
+        entities: vec![],
+    }
+}
+
+/// A farm with the given ID and name `farm<ID>`, owned by twin 1. It is certified, not dedicated
+/// and has no public IPs.
+pub fn farm(id: u32) -> Farm {
+    Farm {
+        version: 1,
+        id,
+        name: format!("farm{id}"),
+        twin_id: 1,
+        pricing_policy_id: 1,
+        certification: FarmCertification::Gold,
+        public_ips: vec![],
+        dedicated_farm: false,
+        farming_policy_limits: None,
+    }
+}
+
+/// A certified node with the given ID in the farm with the given ID, owned by twin `100 + ID`. It
+/// has no resources and no public config.
+pub fn node(id: u32, farm_id: u32) -> Node {
+    Node {
+        version: 1,
+        id,
+        farm_id,
+        twin_id: id + 100,
+        resources: Resources::default(),
+        location: Location {
+            longitude: "4.35".to_string(),
+            latitude: "50.85".to_string(),
+        },
+        country: "Belgium".to_string(),
+        city: "Brussels".to_string(),
+        public_config: None,
+        created: 0,
+        farming_policy_id: 1,
+        interfaces: vec![],
+        certification: NodeCertification::Certified,
+        secure_boot: false,
+        virtualized: false,
+        serial_number: String::new(),
+        connection_price: 0,
+    }
+}
+
+/// A contract with the given ID and state, of twin 1.
+pub fn contract(contract_id: u64, state: ContractState, contract_type: ContractData) -> Contract {
+    Contract {
+        version: 1,
+        state,
+        contract_id,
+        twin_id: 1,
+        contract_type,
+        solution_provider_id: None,
+    }
+}
+
+#[async_trait::async_trait]
+impl RuntimeClient for MockClient {
+    async fn events(&self, _: Option<Hash>) -> Result<Vec<RuntimeEvents>, Error> {
+        Ok(self.events.clone())
+    }
+
+    async fn hash_at_height(&self, block: Option<u32>) -> Result<Option<Hash>, Error> {
+        Ok(match block {
+            Some(height) => self.hashes.get(&height).copied(),
+            None => Some(self.latest_finalized_hash),
+        })
+    }
+
+    /// Blocks are not kept by the mock, so this is always `None`.
+    async fn block(&self, _: Option<Hash>) -> Result<Option<Block>, Error> {
+        Ok(None)
+    }
+
+    async fn latest_finalized_hash(&self) -> Result<Hash, Error> {
+        Ok(self.latest_finalized_hash)
+    }
+
+    async fn latest_block_number(&self) -> Result<u32, Error> {
+        Ok(self.latest_block_number)
+    }
+
+    async fn timestamp(&self, block: Option<Hash>) -> Result<u64, Error> {
+        Ok(block
+            .and_then(|hash| self.timestamps.get(&hash).copied())
+            .unwrap_or(self.timestamp))
+    }
+
+    async fn account_data(
+        &self,
+        account: &AccountId32,
+        _: Option<Hash>,
+    ) -> Result<AccountData, Error> {
+        Ok(self.accounts.get(&account.0).cloned().unwrap_or_default())
+    }
+
+    async fn twin(&self, id: u32, _: Option<Hash>) -> Result<Option<Twin>, Error> {
+        Ok(self.twins.get(&id).cloned())
+    }
+
+    async fn twin_id_by_account(
+        &self,
+        account: &AccountId32,
+        _: Option<Hash>,
+    ) -> Result<Option<u32>, Error> {
+        Ok(self
+            .twins
+            .values()
+            .find(|twin| &twin.account_id == account)
+            .map(|twin| twin.id))
+    }
+
+    async fn twin_count(&self, _: Option<Hash>) -> Result<u32, Error> {
+        Ok(self.twins.keys().max().copied().unwrap_or_default())
+    }
+
+    async fn farm_id_by_name(&self, name: &str, _: Option<Hash>) -> Result<Option<u32>, Error> {
+        Ok(self
+            .farms
+            .values()
+            .find(|farm| farm.name == name)
+            .map(|farm| farm.id))
+    }
+
+    async fn farm(&self, id: u32, _: Option<Hash>) -> Result<Option<Farm>, Error> {
+        Ok(self.farms.get(&id).cloned())
+    }
+
+    async fn farm_payout_address(&self, id: u32, _: Option<Hash>) -> Result<Option<String>, Error> {
+        Ok(self.farm_payout_addresses.get(&id).cloned())
+    }
+
+    async fn farm_count(&self, _: Option<Hash>) -> Result<u32, Error> {
+        Ok(self.farms.keys().max().copied().unwrap_or_default())
+    }
+
+    async fn node_id_by_twin_id(
+        &self,
+        twin_id: u32,
+        _: Option<Hash>,
+    ) -> Result<Option<u32>, Error> {
+        Ok(self
+            .nodes
+            .values()
+            .find(|node| node.twin_id == twin_id)
+            .map(|node| node.id))
+    }
+
+    async fn node(&self, id: u32, _: Option<Hash>) -> Result<Option<Node>, Error> {
+        Ok(self.nodes.get(&id).cloned())
+    }
+
+    async fn farm_nodes(&self, id: u32, _: Option<Hash>) -> Result<Vec<u32>, Error> {
+        let mut nodes: Vec<u32> = self
+            .nodes
+            .values()
+            .filter(|node| node.farm_id == id)
+            .map(|node| node.id)
+            .collect();
+        nodes.sort_unstable();
+        Ok(nodes)
+    }
+
+    async fn node_contracts(&self, id: u32, _: Option<Hash>) -> Result<Vec<u64>, Error> {
+        let mut contracts: Vec<u64> = self
+            .contracts
+            .values()
+            .filter(|contract| !matches!(contract.state, ContractState::Deleted(_)))
+            .filter(|contract| {
+                matches!(&contract.contract_type, ContractData::NodeContract(nc) if nc.node_id == id)
+            })
+            .map(|contract| contract.contract_id)
+            .collect();
+        contracts.sort_unstable();
+        Ok(contracts)
+    }
+
+    async fn rent_contract_for_node(
+        &self,
+        node_id: u32,
+        _: Option<Hash>,
+    ) -> Result<Option<u64>, Error> {
+        Ok(self
+            .contracts
+            .values()
+            .filter(|contract| !matches!(contract.state, ContractState::Deleted(_)))
+            .find(|contract| {
+                matches!(&contract.contract_type, ContractData::RentContract(rc) if rc.node_id == node_id)
+            })
+            .map(|contract| contract.contract_id))
+    }
+
+    async fn node_count(&self, _: Option<Hash>) -> Result<u32, Error> {
+        Ok(self.nodes.keys().max().copied().unwrap_or_default())
+    }
+
+    async fn contract_id_by_name(&self, name: &str, _: Option<Hash>) -> Result<Option<u64>, Error> {
+        Ok(self
+            .contracts
+            .values()
+            .find(|contract| {
+                matches!(&contract.contract_type, ContractData::NameContract(nc) if nc.name == name)
+            })
+            .map(|contract| contract.contract_id))
+    }
+
+    async fn contract_id_by_node_id_and_hash(
+        &self,
+        node_id: u32,
+        deployment_hash: &[u8; 32],
+        _: Option<Hash>,
+    ) -> Result<Option<u64>, Error> {
+        Ok(self
+            .contracts
+            .values()
+            .filter(|contract| !matches!(contract.state, ContractState::Deleted(_)))
+            .find(|contract| {
+                matches!(&contract.contract_type, ContractData::NodeContract(nc)
+                    if nc.node_id == node_id && nc.deployment_hash.0 == *deployment_hash)
+            })
+            .map(|contract| contract.contract_id))
+    }
+
+    async fn contract(&self, id: u64, _: Option<Hash>) -> Result<Option<Contract>, Error> {
+        Ok(self.contracts.get(&id).cloned())
+    }
+
+    async fn contract_resources(
+        &self,
+        id: u64,
+        _: Option<Hash>,
+    ) -> Result<Option<ContractResources>, Error> {
+        Ok(self.contract_resources.get(&id).cloned())
+    }
+
+    async fn contract_billing_information(
+        &self,
+        id: u64,
+        _: Option<Hash>,
+    ) -> Result<Option<ContractBillingInformation>, Error> {
+        Ok(self.contract_billing_information.get(&id).cloned())
+    }
+
+    async fn contract_lock(&self, id: u64, _: Option<Hash>) -> Result<Option<ContractLock>, Error> {
+        Ok(self.contract_locks.get(&id).cloned())
+    }
+
+    async fn solution_provider(
+        &self,
+        id: u64,
+        _: Option<Hash>,
+    ) -> Result<Option<SolutionProvider>, Error> {
+        Ok(self.solution_providers.get(&id).cloned())
+    }
+
+    async fn contract_count(&self, _: Option<Hash>) -> Result<u64, Error> {
+        Ok(self.contracts.keys().max().copied().unwrap_or_default())
+    }
+
+    async fn farming_policy(&self, id: u32, _: Option<Hash>) -> Result<Option<FarmPolicy>, Error> {
+        Ok(self.farming_policies.get(&id).cloned())
+    }
+
+    async fn farming_policy_count(&self, _: Option<Hash>) -> Result<u32, Error> {
+        Ok(self
+            .farming_policies
+            .keys()
+            .max()
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn pricing_policy(
+        &self,
+        id: u32,
+        _: Option<Hash>,
+    ) -> Result<Option<PricingPolicy>, Error> {
+        Ok(self.pricing_policies.get(&id).cloned())
+    }
+}
@@ -1,9 +1,14 @@
 use super::runtime::api::runtime_types::{
+    frame_system::AccountInfo as RuntimeAccountInfo,
+    pallet_balances::AccountData as RuntimeAccountData,
     pallet_smart_contract::types::{
-        Cause as RuntimeCause, Contract as RuntimeContract, ContractData as RuntimeContractData,
+        Cause as RuntimeCause, Contract as RuntimeContract,
+        ContractBillingInformation as RuntimeContractBillingInformation,
+        ContractData as RuntimeContractData, ContractLock as RuntimeContractLock,
         ContractResources as RuntimeContractResources, ContractState as RuntimeContractState,
         NameContract as RuntimeNameContract, NodeContract as RuntimeNodeContract,
-        NruConsumption as RuntimeNruResources, RentContract as RuntimeRentContract,
+        NruConsumption as RuntimeNruResources, Provider as RuntimeProvider,
+        RentContract as RuntimeRentContract, SolutionProvider as RuntimeSolutionProvider,
     },
     pallet_tfgrid::{
         farm::FarmName as RuntimeFarmName,
@@ -15,7 +20,8 @@ use super::runtime::api::runtime_types::{
         twin::TwinIp as RuntimeTwinIP,
         types::{
             EntityProof as RuntimeEntityProof, FarmingPolicy as RuntimeFarmingPolicy,
-            Twin as RuntimeTwin,
+            Policy as RuntimePolicy, PricingPolicy as RuntimePricingPolicy, Twin as RuntimeTwin,
+            Unit as RuntimeUnit,
         },
     },
     sp_core::bounded::bounded_vec::BoundedVec,
@@ -28,13 +34,15 @@ use super::runtime::api::runtime_types::{
     },
 };
 use crate::types::{
-    Cause, Contract, ContractData, ContractResources, ContractState, Domain, EntityProof, Farm,
-    FarmCertification, FarmPolicy, FarmingPolicyLimit, Interface, Location, NameContract, Node,
-    NodeCertification, NodeContract, NruConsumption, PubIPConfig, PublicConfig, PublicIP,
-    RentContract, Resources, Twin,
+    AccountData, Cause, Contract, ContractBillingInformation, ContractData, ContractLock,
+    ContractResources, ContractState, Domain, EntityProof, Farm, FarmCertification, FarmPolicy,
+    FarmingPolicyLimit, Interface, Location, NameContract, Node, NodeCertification, NodeContract,
+    NruConsumption, Phase, Policy, PricingPolicy, Provider, PubIPConfig, PublicConfig, PublicIP,
+    RentContract, Resources, RuntimeEvents, SolutionProvider, Twin, Unit,
 };
 use subxt::utils::AccountId32;
 
+pub type V123AccountInfo = RuntimeAccountInfo<u32, RuntimeAccountData<u128>>;
 pub type V123Twin = RuntimeTwin<RuntimeTwinIP, AccountId32>;
 pub type V123Farm = RuntimeFarm<RuntimeFarmName>;
 pub type V123Node = RuntimeNode<
@@ -44,7 +52,11 @@ pub type V123Node = RuntimeNode<
 >;
 pub type V123Contract = RuntimeContract;
 pub type V123ContractResources = RuntimeContractResources;
+pub type V123ContractBillingInformation = RuntimeContractBillingInformation;
+pub type V123ContractLock = RuntimeContractLock<u128>;
+pub type V123SolutionProvider = RuntimeSolutionProvider<AccountId32>;
 pub type V123FarmingPolicy = RuntimeFarmingPolicy<u32>;
+pub type V123PricingPolicy = RuntimePricingPolicy<AccountId32>;
 
 pub type V123NodeStoredEvent = super::runtime::api::tfgrid_module::events::NodeStored;
 pub type V123NodeUpdatedEvent = super::runtime::api::tfgrid_module::events::NodeUpdated;
@@ -56,6 +68,53 @@ pub type V123ContractUpdatedResourcesEvent =
     super::runtime::api::smart_contract_module::events::UpdatedUsedResources;
 pub type V123ContractNruConsumptionReceivedEvent =
     super::runtime::api::smart_contract_module::events::NruConsumptionReportReceived;
+pub type V123EventRecords = Vec<
+    super::runtime::api::runtime_types::frame_system::EventRecord<
+        super::runtime::api::Event,
+        subxt::utils::H256,
+    >,
+>;
+
+impl From<super::runtime::api::runtime_types::frame_system::Phase> for Phase {
+    fn from(phase: super::runtime::api::runtime_types::frame_system::Phase) -> Self {
+        use super::runtime::api::runtime_types::frame_system::Phase as RuntimePhase;
+
+        match phase {
+            RuntimePhase::ApplyExtrinsic(index) => Phase::ApplyExtrinsic(index),
+            RuntimePhase::Finalization => Phase::Finalization,
+            RuntimePhase::Initialization => Phase::Initialization,
+        }
+    }
+}
+
+/// Convert the event into one of the [`RuntimeEvents`] known to the client, if it is one.
+pub fn known_event(event: super::runtime::api::Event) -> Option<RuntimeEvents> {
+    use super::runtime::api::{smart_contract_module, tfgrid_module, Event};
+
+    match event {
+        Event::TfgridModule(tfgrid_module::Event::NodeStored(node)) => {
+            Some(RuntimeEvents::NodeStoredEvent(node.into()))
+        }
+        Event::TfgridModule(tfgrid_module::Event::NodeUpdated(node)) => {
+            Some(RuntimeEvents::NodeUpdatedEvent(node.into()))
+        }
+        Event::TfgridModule(tfgrid_module::Event::NodeUptimeReported(node_id, now, uptime)) => {
+            Some(RuntimeEvents::NodeUptimeReported(node_id, now, uptime))
+        }
+        Event::SmartContractModule(smart_contract_module::Event::ContractCreated(contract)) => {
+            Some(RuntimeEvents::ContractCreated(contract.into()))
+        }
+        Event::SmartContractModule(smart_contract_module::Event::UpdatedUsedResources(
+            resources,
+        )) => Some(RuntimeEvents::ContractUsedResourcesUpdated(
+            resources.into(),
+        )),
+        Event::SmartContractModule(smart_contract_module::Event::NruConsumptionReportReceived(
+            consumption,
+        )) => Some(RuntimeEvents::NruConsumptionReceived(consumption.into())),
+        _ => None,
+    }
+}
 
 impl From<RuntimeTwin<RuntimeTwinIP, AccountId32>> for Twin {
     fn from(rt: RuntimeTwin<RuntimeTwinIP, AccountId32>) -> Self {
@@ -87,8 +146,7 @@ impl From<RuntimeEntityProof> for EntityProof {
         } = rtep;
         EntityProof {
             entity_id,
-            // SAFETY: signatures are always 64 bytes in the current allowed signature schemes.
-            signature: signature.try_into().unwrap(),
+            signature,
         }
     }
 }
@@ -190,6 +248,61 @@ impl From<RuntimeFarmingPolicy<u32>> for FarmPolicy {
     }
 }
 
+impl From<RuntimePricingPolicy<AccountId32>> for PricingPolicy {
+    fn from(rpp: RuntimePricingPolicy<AccountId32>) -> Self {
+        let RuntimePricingPolicy {
+            version,
+            id,
+            name,
+            su,
+            cu,
+            nu,
+            ipu,
+            unique_name,
+            domain_name,
+            foundation_account,
+            certified_sales_account,
+            discount_for_dedication_nodes,
+        } = rpp;
+        PricingPolicy {
+            version,
+            id,
+            // SAFETY: Chain ensures this can only be valid ASCII.
+            name: unsafe { String::from_utf8_unchecked(name) },
+            su: su.into(),
+            cu: cu.into(),
+            nu: nu.into(),
+            ipu: ipu.into(),
+            unique_name: unique_name.into(),
+            domain_name: domain_name.into(),
+            foundation_account,
+            certified_sales_account,
+            discount_for_dedication_nodes,
+        }
+    }
+}
+
+impl From<RuntimePolicy> for Policy {
+    fn from(rp: RuntimePolicy) -> Self {
+        Policy {
+            value: rp.value,
+            unit: rp.unit.into(),
+        }
+    }
+}
+
+impl From<RuntimeUnit> for Unit {
+    fn from(ru: RuntimeUnit) -> Self {
+        match ru {
+            RuntimeUnit::Bytes => Unit::Bytes,
+            RuntimeUnit::Kilobytes => Unit::Kilobytes,
+            RuntimeUnit::Megabytes => Unit::Megabytes,
+            RuntimeUnit::Gigabytes => Unit::Gigabytes,
+            RuntimeUnit::Terrabytes => Unit::Terabytes,
+        }
+    }
+}
+
 impl From<RuntimeNodeCertification> for NodeCertification {
     fn from(rnc: RuntimeNodeCertification) -> Self {
         match rnc {
@@ -445,6 +558,65 @@ impl From<RuntimeRentContract> for RentContract {
     }
 }
 
+impl From<RuntimeContractBillingInformation> for ContractBillingInformation {
+    fn from(rcbi: RuntimeContractBillingInformation) -> Self {
+        let RuntimeContractBillingInformation {
+            previous_nu_reported,
+            last_updated,
+            amount_unbilled,
+        } = rcbi;
+        ContractBillingInformation {
+            previous_nu_reported,
+            last_updated,
+            amount_unbilled,
+        }
+    }
+}
+
+impl From<RuntimeContractLock<u128>> for ContractLock {
+    fn from(rcl: RuntimeContractLock<u128>) -> Self {
+        let RuntimeContractLock {
+            amount_locked,
+            lock_updated,
+            cycles,
+        } = rcl;
+        ContractLock {
+            amount_locked,
+            lock_updated,
+            cycles,
+        }
+    }
+}
+
+impl From<RuntimeSolutionProvider<AccountId32>> for SolutionProvider {
+    fn from(rsp: RuntimeSolutionProvider<AccountId32>) -> Self {
+        let RuntimeSolutionProvider {
+            solution_provider_id,
+            providers,
+            description,
+            link,
+            approved,
+        } = rsp;
+        SolutionProvider {
+            solution_provider_id,
+            providers: providers.into_iter().map(|p| p.into()).collect(),
+            // The chain doesn't validate these, so they can contain any bytes.
+            description: String::from_utf8_lossy(&description).into_owned(),
+            link: String::from_utf8_lossy(&link).into_owned(),
+            approved,
+        }
+    }
+}
+
+impl From<RuntimeProvider<AccountId32>> for Provider {
+    fn from(rp: RuntimeProvider<AccountId32>) -> Self {
+        Provider {
+            who: rp.who,
+            take: rp.take,
+        }
+    }
+}
+
 impl From<RuntimeContractResources> for ContractResources {
     fn from(rcr: RuntimeContractResources) -> Self {
         let RuntimeContractResources { contract_id, used } = rcr;
@@ -471,3 +643,20 @@ impl From<RuntimeNruResources> for NruConsumption {
         }
     }
 }
+
+impl From<V123AccountInfo> for AccountData {
+    fn from(info: V123AccountInfo) -> Self {
+        let RuntimeAccountData {
+            free,
+            reserved,
+            misc_frozen,
+            fee_frozen,
+        } = info.data;
+        AccountData {
+            free,
+            reserved,
+            misc_frozen,
+            fee_frozen,
+        }
+    }
+}
@@ -1,12 +1,28 @@
-use std::net::IpAddr;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
 /// The hash type used on the TfChain.
 pub type Hash = subxt::utils::H256;
 use subxt::utils::AccountId32;
+/// The signer used to sign extrinsics on the TfChain.
+pub type Signer = subxt::tx::PairSigner<subxt::PolkadotConfig, sp_core::sr25519::Pair>;
 /// Public Key type, this is a placeholder.
 pub type PublicKey = [u8; 32];
 /// Signature type, this is a placeholder.
 pub type Signature = [u8; 64];
 pub type BlockNumber = subxt::rpc::types::BlockNumber;
+/// A block on the TfChain, consisting of its header and encoded extrinsics.
+pub type Block = subxt::rpc::types::ChainBlock<subxt::PolkadotConfig>;
+/// The version of the runtime of the TfChain at a block.
+pub type RuntimeVersion = subxt::rpc::types::RuntimeVersion;
+/// The spec versions of the runtimes whose data can be decoded by the clients.
+pub const SUPPORTED_SPEC_VERSIONS: std::ops::RangeInclusive<u32> = 115..=123;
+/// The result of dry running an extrinsic: whether it would be included in a block and
+/// dispatched successfully.
+pub type DryRunResult = subxt::rpc::types::DryRunResult;
+/// A raw storage key, i.e. the hashed pallet and item name followed by the hashed map keys.
+pub use subxt::rpc::types::StorageKey;
+/// The block header type used on the TfChain.
+pub type Header = <subxt::PolkadotConfig as subxt::Config>::Header;
 
 pub const TFGRID_MODULE: &str = "TfgridModule";
 pub const NODE_STORED: &str = "NodeStored";
@@ -16,11 +32,22 @@ pub const FARMING_POLICIES: &str = "FarmingPoliciesMap";
 pub const FARMING_POLICY_ID: &str = "FarmingPolicyID";
 pub const NODE_ID: &str = "NodeID";
 pub const NODES: &str = "Nodes";
+pub const NODES_BY_FARM_ID: &str = "NodesByFarmID";
+pub const NODE_ID_BY_TWIN_ID: &str = "NodeIdByTwinID";
 pub const FARM_ID: &str = "FarmID";
 pub const FARM_PAYOUT_V2_ADDRESS: &str = "FarmPayoutV2AddressByFarmID";
 pub const TWINS: &str = "Twins";
 pub const TWIN_ID: &str = "TwinID";
+pub const TWIN_ID_BY_ACCOUNT_ID: &str = "TwinIdByAccountID";
 pub const FARMS: &str = "Farms";
+pub const FARM_ID_BY_NAME: &str = "FarmIdByName";
+pub const PRICING_POLICIES: &str = "PricingPolicies";
+pub const CREATE_NODE: &str = "create_node";
+pub const UPDATE_TWIN: &str = "update_twin";
+pub const DELETE_TWIN: &str = "delete_twin";
+pub const ADD_FARM_IP: &str = "add_farm_ip";
+pub const REMOVE_FARM_IP: &str = "remove_farm_ip";
+pub const REPORT_UPTIME: &str = "report_uptime";
 
 pub const SMART_CONTRACT_MODULE: &str = "SmartContractModule";
 pub const UPDATE_USED_RESOURCES: &str = "UpdatedUsedResources";
@@ -28,13 +55,69 @@ pub const NRU_CONSUMPTION_RECEIVED: &str = "NruConsumptionReportReceived";
 pub const CONTRACT_CREATED: &str = "ContractCreated";
 pub const NODE_CONTRACT_CANCELLED: &str = "NodeContractCanceled";
 pub const NODE_CONTRACT_RESOURCES: &str = "NodeContractResources";
+pub const CONTRACT_BILLING_INFORMATION_BY_ID: &str = "ContractBillingInformationByID";
+pub const SOLUTION_PROVIDERS: &str = "SolutionProviders";
+pub const CONTRACT_LOCK: &str = "ContractLock";
+pub const CREATE_NODE_CONTRACT: &str = "create_node_contract";
+pub const CREATE_RENT_CONTRACT: &str = "create_rent_contract";
+pub const CANCEL_CONTRACT: &str = "cancel_contract";
 pub const CONTRACT_ID: &str = "ContractID";
+pub const CONTRACT_ID_BY_NAME_REGISTRATION: &str = "ContractIDByNameRegistration";
+pub const CONTRACT_ID_BY_NODE_ID_AND_HASH: &str = "ContractIDByNodeIDAndHash";
 pub const CONTRACTS: &str = "Contracts";
+pub const ACTIVE_NODE_CONTRACTS: &str = "ActiveNodeContracts";
+pub const ACTIVE_RENT_CONTRACT_FOR_NODE: &str = "ActiveRentContractForNode";
+
+pub const ACCOUNT: &str = "Account";
 
 pub const TIMESTAMP_MODULE: &str = "Timestamp";
 pub const TIMESTAMP_NOW: &str = "Now";
 
+pub const SYSTEM_MODULE: &str = "System";
+pub const EXTRINSIC_FAILED: &str = "ExtrinsicFailed";
+pub const EVENTS: &str = "Events";
+
+pub const UTILITY_MODULE: &str = "Utility";
+pub const BATCH_ALL: &str = "batch_all";
+
+pub const SUDO_MODULE: &str = "Sudo";
+pub const SUDO: &str = "sudo";
+
+pub const TRANSACTION_PAYMENT_QUERY_INFO: &str = "TransactionPaymentApi_query_info";
+
+pub const BALANCES_MODULE: &str = "Balances";
+pub const TRANSFER: &str = "transfer";
+pub const TRANSFER_KEEP_ALIVE: &str = "transfer_keep_alive";
+
+/// The balances of an account.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountData {
+    /// The balance which is not reserved. Part of it can still be frozen, so it is not necessarily
+    /// spendable.
+    pub free: u128,
+    /// The balance which is reserved, e.g. as a deposit, and can't be spent.
+    pub reserved: u128,
+    /// The part of the free balance which can't be spent, except to pay for fees.
+    pub misc_frozen: u128,
+    /// The part of the free balance which can't be spent to pay for fees.
+    pub fee_frozen: u128,
+}
+
+impl AccountData {
+    /// The balance which is reserved, e.g. as a deposit, and can't be spent.
+    pub fn reserved_balance(&self) -> u128 {
+        self.reserved
+    }
+
+    /// The total balance of the account, i.e. the free and reserved balance.
+    pub fn total_balance(&self) -> u128 {
+        self.free.saturating_add(self.reserved)
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Twin {
     pub version: u32,
     pub id: u32,
@@ -44,7 +127,14 @@ pub struct Twin {
     pub entities: Vec<EntityProof>,
 }
 
+impl fmt::Display for Twin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "twin {} ({})", self.id, self.ip)
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Farm {
     pub version: u32,
     pub id: u32,
@@ -57,7 +147,21 @@ pub struct Farm {
     pub farming_policy_limits: Option<FarmingPolicyLimit>,
 }
 
+impl fmt::Display for Farm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "farm {} \"{}\" (twin {}, {} public ips)",
+            self.id,
+            self.name,
+            self.twin_id,
+            self.public_ips.len()
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PublicIP {
     // Not ideal but there is no type in std to represent IP+subnet currently, could make this a
     // tuple of (IP, mask).
@@ -68,6 +172,7 @@ pub struct PublicIP {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FarmingPolicyLimit {
     pub farming_policy_id: u32,
     pub cu: Option<u64>,
@@ -78,12 +183,14 @@ pub struct FarmingPolicyLimit {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FarmCertification {
     Gold,
     NotCertified,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     pub version: u32,
     pub id: u32,
@@ -104,7 +211,34 @@ pub struct Node {
     pub connection_price: u32,
 }
 
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node {} (farm {}, {})",
+            self.id, self.farm_id, self.resources
+        )
+    }
+}
+
+/// The details of a node to register on the grid, mirroring the `create_node` call of the tfgrid
+/// pallet.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeRegistration {
+    pub farm_id: u32,
+    pub resources: Resources,
+    pub location: Location,
+    pub country: String,
+    pub city: String,
+    pub interfaces: Vec<Interface>,
+    pub secure_boot: bool,
+    pub virtualized: bool,
+    pub serial_number: Option<String>,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interface {
     pub name: String,
     // This really should be a [u8;6], but the chain saves this as a string currently;
@@ -112,21 +246,78 @@ pub struct Interface {
     pub ips: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+/// An amount of capacity, e.g. the total resources of a node or the resources used by a contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Resources {
+    /// HDD storage, in bytes.
     pub hru: u64,
+    /// SSD storage, in bytes.
     pub sru: u64,
+    /// The amount of virtual CPU cores.
     pub cru: u64,
+    /// Memory, in bytes.
     pub mru: u64,
 }
 
+impl Resources {
+    /// The resources which are left when `used` is taken from these resources, e.g. the free
+    /// capacity of a node given the sum of the resources used by its contracts. Every resource is
+    /// at least 0.
+    pub fn free(&self, used: &Resources) -> Resources {
+        Resources {
+            hru: self.hru.saturating_sub(used.hru),
+            sru: self.sru.saturating_sub(used.sru),
+            cru: self.cru.saturating_sub(used.cru),
+            mru: self.mru.saturating_sub(used.mru),
+        }
+    }
+}
+
+impl std::ops::Add for Resources {
+    type Output = Resources;
+
+    fn add(self, other: Resources) -> Resources {
+        Resources {
+            hru: self.hru.saturating_add(other.hru),
+            sru: self.sru.saturating_add(other.sru),
+            cru: self.cru.saturating_add(other.cru),
+            mru: self.mru.saturating_add(other.mru),
+        }
+    }
+}
+
+impl std::ops::AddAssign for Resources {
+    fn add_assign(&mut self, other: Resources) {
+        *self = *self + other;
+    }
+}
+
+impl std::iter::Sum for Resources {
+    fn sum<I: Iterator<Item = Resources>>(iter: I) -> Resources {
+        iter.fold(Resources::default(), |total, resources| total + resources)
+    }
+}
+
+impl fmt::Display for Resources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cru {}, mru {}, sru {}, hru {}",
+            self.cru, self.mru, self.sru, self.hru
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
     pub longitude: String,
     pub latitude: String,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contract {
     pub version: u32,
     pub state: ContractState,
@@ -136,27 +327,62 @@ pub struct Contract {
     pub solution_provider_id: Option<u64>,
 }
 
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "contract {} ({}, {})",
+            self.contract_id, self.state, self.contract_type
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ContractState {
     Created,
     Deleted(Cause),
     GracePeriod(u64),
 }
 
+impl fmt::Display for ContractState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractState::Created => f.write_str("created"),
+            ContractState::Deleted(Cause::CanceledByUser) => f.write_str("canceled by user"),
+            ContractState::Deleted(Cause::OutOfFunds) => f.write_str("deleted, out of funds"),
+            ContractState::GracePeriod(block) => write!(f, "in grace period since block {block}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cause {
     CanceledByUser,
     OutOfFunds,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ContractData {
     NodeContract(NodeContract),
     NameContract(NameContract),
     RentContract(RentContract),
 }
 
+impl fmt::Display for ContractData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractData::NodeContract(nc) => write!(f, "node contract on node {}", nc.node_id),
+            ContractData::NameContract(nc) => write!(f, "name contract \"{}\"", nc.name),
+            ContractData::RentContract(rc) => write!(f, "rent contract on node {}", rc.node_id),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeContract {
     pub node_id: u32,
     pub deployment_hash: Hash,
@@ -166,16 +392,19 @@ pub struct NodeContract {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NameContract {
     pub name: String,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RentContract {
     pub node_id: u32,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PublicConfig {
     pub ip4: PubIPConfig,
     pub ip6: Option<PubIPConfig>,
@@ -183,27 +412,77 @@ pub struct PublicConfig {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PubIPConfig {
     pub ip: String,
     pub gw: String,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Domain(pub String);
 
+/// A solution provider, which receives part of the payments for the contracts referencing it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolutionProvider {
+    pub solution_provider_id: u64,
+    pub providers: Vec<Provider>,
+    pub description: String,
+    pub link: String,
+    /// Whether the solution provider has been approved, only then it can be used by contracts.
+    pub approved: bool,
+}
+
+/// An account which receives part of the payments for a solution provider.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Provider {
+    pub who: AccountId32,
+    /// The percentage of the payments the account receives.
+    pub take: u8,
+}
+
+/// The billing state of a contract.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContractBillingInformation {
+    /// The amount of network units reported in the previous report.
+    pub previous_nu_reported: u64,
+    /// The timestamp of the last time the contract was billed.
+    pub last_updated: u64,
+    /// The amount which is due but not yet billed, in the smallest unit of TFT.
+    pub amount_unbilled: u64,
+}
+
+/// The funds locked by a contract, which are transferred when the contract is billed.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContractLock {
+    /// The amount currently locked, in the smallest unit of TFT.
+    pub amount_locked: u128,
+    /// The timestamp of the last time the lock was updated.
+    pub lock_updated: u64,
+    /// The amount of billing cycles since the lock was last released.
+    pub cycles: u16,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContractResources {
     pub contract_id: u64,
     pub used: Resources,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityProof {
     pub entity_id: u32,
     pub signature: Vec<u8>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FarmPolicy {
     pub version: u32,
     pub id: u32,
@@ -221,13 +500,58 @@ pub struct FarmPolicy {
     pub farm_certification: FarmCertification,
 }
 
+/// The prices used to bill contracts.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PricingPolicy {
+    pub version: u32,
+    pub id: u32,
+    pub name: String,
+    /// The price of a storage unit.
+    pub su: Policy,
+    /// The price of a compute unit.
+    pub cu: Policy,
+    /// The price of network usage.
+    pub nu: Policy,
+    /// The price of a public IP.
+    pub ipu: Policy,
+    /// The price of a name contract.
+    pub unique_name: Policy,
+    /// The price of a domain name.
+    pub domain_name: Policy,
+    pub foundation_account: AccountId32,
+    pub certified_sales_account: AccountId32,
+    /// The discount in percent for contracts on dedicated nodes.
+    pub discount_for_dedication_nodes: u8,
+}
+
+/// The price of an amount of `unit` of a resource.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Policy {
+    pub value: u32,
+    pub unit: Unit,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unit {
+    Bytes,
+    Kilobytes,
+    Megabytes,
+    Gigabytes,
+    Terabytes,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NodeCertification {
     Certified,
     Diy,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NruConsumption {
     pub contract_id: u64,
     pub timestamp: u64,
@@ -235,7 +559,112 @@ pub struct NruConsumption {
     pub nru: u64,
 }
 
+/// A call which can be submitted as an extrinsic by the client, on its own or as part of a batch.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Call {
+    CreateNode(NodeRegistration),
+    UpdateTwin {
+        ip: IpAddr,
+    },
+    DeleteTwin {
+        twin_id: u32,
+    },
+    /// Add a public IP to a farm. The IP includes the prefix length of the subnet, e.g.
+    /// `185.206.122.33/24`.
+    AddFarmIp {
+        farm_id: u32,
+        ip: String,
+        gateway: Ipv4Addr,
+    },
+    RemoveFarmIp {
+        farm_id: u32,
+        ip: String,
+    },
+    ReportUptime {
+        uptime: u64,
+    },
+    CreateNodeContract {
+        node_id: u32,
+        deployment_hash: [u8; 32],
+        deployment_data: Vec<u8>,
+        public_ips: u32,
+        solution_provider_id: Option<u64>,
+    },
+    CreateRentContract {
+        node_id: u32,
+        solution_provider_id: Option<u64>,
+    },
+    CancelContract {
+        contract_id: u64,
+    },
+    Transfer {
+        dest: AccountId32,
+        amount: u128,
+    },
+    TransferKeepAlive {
+        dest: AccountId32,
+        amount: u128,
+    },
+}
+
+/// The outcome of a submitted extrinsic.
+#[derive(Debug)]
+pub struct ExtrinsicOutcome {
+    pub extrinsic_hash: Hash,
+    /// The hash of the block the extrinsic was included in, if the client waited for this.
+    pub block_hash: Option<Hash>,
+    /// The known events emitted while applying the extrinsic. Events emitted by other extrinsics
+    /// in the same block are not included.
+    pub events: Vec<RuntimeEvents>,
+}
+
+/// The health of the node the client is connected to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainHealth {
+    /// The amount of peers the node is connected to.
+    pub peers: usize,
+    /// Whether the node is still syncing the chain.
+    pub is_syncing: bool,
+    /// Whether the node is expected to have peers. This might be false for local chains.
+    pub should_have_peers: bool,
+    /// The height of the latest finalized block known to the node.
+    pub finalized_height: u32,
+}
+
+/// The phase of the execution of a block in which an event was emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// While applying the extrinsic at this index in the block.
+    ApplyExtrinsic(u32),
+    /// While finalizing the block.
+    Finalization,
+    /// While initializing the block.
+    Initialization,
+}
+
+/// A known event, together with the phase it was emitted in and its topics.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedEvent {
+    pub phase: Phase,
+    pub event: RuntimeEvents,
+    pub topics: Vec<Hash>,
+}
+
+/// The known events emitted in a block.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockEvents {
+    pub number: u32,
+    pub hash: Hash,
+    pub events: Vec<RuntimeEvents>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RuntimeEvents {
     NodeStoredEvent(Node),
     NodeUpdatedEvent(Node),
@@ -244,3 +673,126 @@ pub enum RuntimeEvents {
     ContractUsedResourcesUpdated(ContractResources),
     NruConsumptionReceived(NruConsumption),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    fn resources(hru: u64, sru: u64, cru: u64, mru: u64) -> Resources {
+        Resources { hru, sru, cru, mru }
+    }
+
+    #[test]
+    fn resources_are_added_saturating() {
+        assert_eq!(
+            resources(1, 2, 3, u64::MAX) + resources(10, 20, 30, 1),
+            resources(11, 22, 33, u64::MAX)
+        );
+
+        let mut total = resources(1, 1, 1, 1);
+        total += resources(1, 2, 3, 4);
+        assert_eq!(total, resources(2, 3, 4, 5));
+    }
+
+    #[test]
+    fn resources_are_summed() {
+        let used = [resources(1, 2, 3, 4), resources(10, 20, 30, 40)];
+        assert_eq!(
+            used.into_iter().sum::<Resources>(),
+            resources(11, 22, 33, 44)
+        );
+        assert_eq!(
+            std::iter::empty::<Resources>().sum::<Resources>(),
+            Resources::default()
+        );
+    }
+
+    #[test]
+    fn free_resources_are_at_least_zero() {
+        let total = resources(100, 50, 8, 16);
+        assert_eq!(
+            total.free(&resources(40, 60, 2, 16)),
+            resources(60, 0, 6, 0)
+        );
+    }
+
+    #[test]
+    fn account_balances() {
+        let data = AccountData {
+            free: 100,
+            reserved: 20,
+            misc_frozen: 50,
+            fee_frozen: 50,
+        };
+        assert_eq!(data.reserved_balance(), 20);
+        assert_eq!(data.total_balance(), 120);
+
+        let data = AccountData {
+            free: u128::MAX,
+            reserved: 1,
+            ..Default::default()
+        };
+        assert_eq!(data.total_balance(), u128::MAX);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            resources(4, 3, 2, 1).to_string(),
+            "cru 2, mru 1, sru 3, hru 4"
+        );
+        let node = Node {
+            resources: resources(4, 3, 2, 1),
+            ..mock::node(12, 3)
+        };
+        assert_eq!(
+            node.to_string(),
+            "node 12 (farm 3, cru 2, mru 1, sru 3, hru 4)"
+        );
+
+        assert_eq!(mock::twin(40).to_string(), "twin 40 (::1)");
+
+        let farm = Farm {
+            name: "freefarm".to_string(),
+            twin_id: 40,
+            public_ips: vec![PublicIP {
+                ip: "185.206.122.33/24".to_string(),
+                gateway: "185.206.122.1".to_string(),
+                contract_id: 0,
+            }],
+            ..mock::farm(3)
+        };
+        assert_eq!(
+            farm.to_string(),
+            "farm 3 \"freefarm\" (twin 40, 1 public ips)"
+        );
+
+        let contract = mock::contract(
+            7,
+            ContractState::GracePeriod(1000),
+            ContractData::RentContract(RentContract { node_id: 12 }),
+        );
+        assert_eq!(
+            contract.to_string(),
+            "contract 7 (in grace period since block 1000, rent contract on node 12)"
+        );
+
+        assert_eq!(ContractState::Created.to_string(), "created");
+        assert_eq!(
+            ContractState::Deleted(Cause::CanceledByUser).to_string(),
+            "canceled by user"
+        );
+        assert_eq!(
+            ContractState::Deleted(Cause::OutOfFunds).to_string(),
+            "deleted, out of funds"
+        );
+        assert_eq!(
+            ContractData::NameContract(NameContract {
+                name: "example".to_string()
+            })
+            .to_string(),
+            "name contract \"example\""
+        );
+    }
+}